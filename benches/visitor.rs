@@ -0,0 +1,48 @@
+//! Times evaluation of long `1 + 1 + ... + 1` chains. Each `+` nests the chain
+//! one level deeper, so this mostly measures how the interpreter walks the
+//! tree. Run with `cargo bench --bench visitor`.
+
+use std::time::{Duration, Instant};
+
+use rlox::{ast::Stmt, interpreter::Interpreter, parser::Parser, scanner::Scanner};
+
+const ITERATIONS: u32 = 20;
+
+fn parse_chain(terms: usize) -> Vec<Stmt> {
+    let mut source = "1".to_string();
+    for _ in 1..terms {
+        source.push_str(" + 1");
+    }
+    source.push(';');
+
+    match Parser::new(Scanner::new(source)).parse() {
+        Ok(statements) => statements,
+        Err(error) => {
+            error.report();
+            panic!("benchmark source failed to parse");
+        }
+    }
+}
+
+fn main() {
+    for terms in [100, 250, 500, 1000] {
+        let statements = parse_chain(terms);
+        let mut total = Duration::ZERO;
+        for _ in 0..ITERATIONS {
+            let interpreter = Interpreter::new();
+
+            let start = Instant::now();
+            if let Err(error) = interpreter.interpret(&statements) {
+                error.report();
+                panic!("benchmark source failed to run");
+            }
+            total += start.elapsed();
+        }
+
+        println!(
+            "chain of {:>4} terms: {:>10.1?} per evaluation",
+            terms,
+            total / ITERATIONS
+        );
+    }
+}
@@ -0,0 +1,64 @@
+//! The allocation budget behind `Interpreter::set_allocation_limit`. The
+//! interpreter charges the values it creates, and the built-in natives charge
+//! the ones they build. A value that is only passed along, like an item
+//! popped off a list, was charged when it was built and isn't charged again.
+
+use std::{cell::Cell, mem};
+
+use crate::{
+    errors::{Error, LoxResult},
+    interpreter::LoxValue,
+    map::MapKey,
+    scanner::token::LiteralType,
+};
+
+#[derive(Default)]
+pub(crate) struct Allocations {
+    allocated: Cell<usize>,
+    limit: Cell<Option<usize>>,
+}
+
+impl Allocations {
+    pub(crate) fn allocated(&self) -> usize {
+        self.allocated.get()
+    }
+
+    pub(crate) fn set_limit(&self, bytes: Option<usize>) {
+        self.limit.set(bytes);
+    }
+
+    /// Fails with `Error::OutOfMemory` if allocating `bytes` more would go
+    /// over the limit, without charging them.
+    pub(crate) fn check(&self, bytes: usize) -> LoxResult<()> {
+        match self.limit.get() {
+            Some(limit) if self.allocated.get().saturating_add(bytes) > limit => {
+                Err(Error::OutOfMemory)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Charges a newly built `value` and hands it back.
+    pub(crate) fn charged(&self, value: LoxValue) -> LoxResult<LoxValue> {
+        let size = match &value {
+            LoxValue::Literal(LiteralType::LString(string)) => string.len(),
+            LoxValue::List(list) => list.borrow().len() * mem::size_of::<LoxValue>(),
+            LoxValue::Map(map) => {
+                map.borrow().len() * (mem::size_of::<MapKey>() + mem::size_of::<LoxValue>())
+            }
+            _ => 0,
+        };
+
+        self.charge(size)?;
+        Ok(value)
+    }
+
+    /// Charges `bytes`, failing with `Error::OutOfMemory` once the total goes
+    /// over the limit.
+    pub(crate) fn charge(&self, bytes: usize) -> LoxResult<()> {
+        let result = self.check(bytes);
+        self.allocated
+            .set(self.allocated.get().saturating_add(bytes));
+        result
+    }
+}
@@ -3,51 +3,275 @@
 use crate::scanner::token::{LiteralType, Token};
 
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", content = "fields")
+)]
 pub enum Expr {
     Assign(Token, Box<Expr>),
     Binary(Box<Expr>, Token, Box<Expr>),
+    /// The callee, the closing parenthesis (for error lines) and the arguments.
+    Call(Box<Expr>, Token, Vec<Expr>),
+    Get(Box<Expr>, Token),
     Grouping(Box<Expr>),
+    /// The indexed value, the closing bracket (for error lines) and the index.
+    Index(Box<Expr>, Token, Box<Expr>),
+    List(Vec<Expr>),
     Literal(LiteralType),
+    /// The closing brace (for error lines) and the key-value pairs.
+    Map(Token, Vec<(Expr, Expr)>),
+    Set(Box<Expr>, Token, Box<Expr>),
+    /// `object[index] = value`, with the closing bracket as in `Index`.
+    SetIndex(Box<Expr>, Token, Box<Expr>, Box<Expr>),
     Unary(Token, Box<Expr>),
     Variable(Token),
 }
 
+impl Expr {
+    /// The line of the first token found in the expression, for errors that
+    /// aren't tied to any one token. Literals carry no line.
+    pub(crate) fn line(&self) -> Option<usize> {
+        let mut expr = self;
+        loop {
+            match expr {
+                Expr::Assign(token, _)
+                | Expr::Binary(_, token, _)
+                | Expr::Call(_, token, _)
+                | Expr::Get(_, token)
+                | Expr::Index(_, token, _)
+                | Expr::Map(token, _)
+                | Expr::Set(_, token, _)
+                | Expr::SetIndex(_, token, _, _)
+                | Expr::Unary(token, _)
+                | Expr::Variable(token) => return Some(token.line),
+                Expr::Grouping(inner) => expr = inner,
+                Expr::List(items) => expr = items.first()?,
+                Expr::Literal(_) => return None,
+            }
+        }
+    }
+}
+
 pub trait Visitor<T> {
-    fn handle_expr(&self, expr: Expr) -> T {
+    fn handle_expr(&self, expr: &Expr) -> T {
         match expr {
             Expr::Assign(token, expr) => self.visit_assign_expr(token, expr),
             Expr::Binary(left, token, right) => self.visit_binary_expr(left, token, right),
+            Expr::Call(callee, paren, arguments) => self.visit_call_expr(callee, paren, arguments),
+            Expr::Get(object, name) => self.visit_get_expr(object, name),
+            Expr::Index(object, bracket, index) => self.visit_index_expr(object, bracket, index),
+            Expr::List(items) => self.visit_list_expr(items),
             Expr::Literal(literal) => self.visit_literal_expr(literal),
+            Expr::Map(brace, entries) => self.visit_map_expr(brace, entries),
             Expr::Grouping(expr) => self.visit_grouping_expr(expr),
+            Expr::Set(object, name, value) => self.visit_set_expr(object, name, value),
+            Expr::SetIndex(object, bracket, index, value) => {
+                self.visit_set_index_expr(object, bracket, index, value)
+            }
             Expr::Unary(token, expr) => self.visit_unary_expr(token, expr),
             Expr::Variable(token) => self.visit_variable_expr(token),
         }
     }
 
-    fn visit_assign_expr(&self, token: Token, expr: Box<Expr>) -> T;
-    fn visit_binary_expr(&self, left: Box<Expr>, token: Token, right: Box<Expr>) -> T;
-    fn visit_grouping_expr(&self, expr: Box<Expr>) -> T;
-    fn visit_literal_expr(&self, literal: LiteralType) -> T;
-    fn visit_unary_expr(&self, token: Token, expr: Box<Expr>) -> T;
-    fn visit_variable_expr(&self, token: Token) -> T;
+    fn visit_assign_expr(&self, token: &Token, expr: &Expr) -> T;
+    fn visit_binary_expr(&self, left: &Expr, token: &Token, right: &Expr) -> T;
+    fn visit_call_expr(&self, callee: &Expr, paren: &Token, arguments: &[Expr]) -> T;
+    fn visit_get_expr(&self, object: &Expr, name: &Token) -> T;
+    fn visit_grouping_expr(&self, expr: &Expr) -> T;
+    fn visit_index_expr(&self, object: &Expr, bracket: &Token, index: &Expr) -> T;
+    fn visit_list_expr(&self, items: &[Expr]) -> T;
+    fn visit_literal_expr(&self, literal: &LiteralType) -> T;
+    fn visit_map_expr(&self, brace: &Token, entries: &[(Expr, Expr)]) -> T;
+    fn visit_set_expr(&self, object: &Expr, name: &Token, value: &Expr) -> T;
+    fn visit_set_index_expr(&self, object: &Expr, bracket: &Token, index: &Expr, value: &Expr)
+        -> T;
+    fn visit_unary_expr(&self, token: &Token, expr: &Expr) -> T;
+    fn visit_variable_expr(&self, token: &Token) -> T;
 }
 
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", content = "fields")
+)]
 pub enum Stmt {
+    Block(Vec<Stmt>),
     Expression(Box<Expr>),
+    /// `for (name in iterable) body`.
+    ForIn(Token, Box<Expr>, Box<Stmt>),
     Print(Box<Expr>),
     Var(Token, Option<Box<Expr>>),
 }
 
-pub trait StmtVisitor {
-    fn handle_stmt(&self, stmt: Stmt) {
+impl Stmt {
+    /// The line of the first token found in the statement; see `Expr::line`.
+    pub(crate) fn line(&self) -> Option<usize> {
+        let mut stmt = self;
+        loop {
+            match stmt {
+                Stmt::Block(statements) => stmt = statements.first()?,
+                Stmt::Expression(expr) | Stmt::Print(expr) => return expr.line(),
+                Stmt::ForIn(token, _, _) | Stmt::Var(token, _) => return Some(token.line),
+            }
+        }
+    }
+}
+
+pub trait StmtVisitor<T> {
+    fn handle_stmt(&self, stmt: &Stmt) -> T {
         match stmt {
+            Stmt::Block(statements) => self.visit_block_stmt(statements),
             Stmt::Expression(expr) => self.visit_expression_stmt(expr),
+            Stmt::ForIn(name, iterable, body) => self.visit_for_in_stmt(name, iterable, body),
             Stmt::Print(expr) => self.visit_print_stmt(expr),
-            Stmt::Var(token, expr) => self.visit_var_stmt(token, expr),
+            Stmt::Var(token, expr) => self.visit_var_stmt(token, expr.as_deref()),
+        }
+    }
+
+    fn visit_block_stmt(&self, statements: &[Stmt]) -> T;
+    fn visit_expression_stmt(&self, expr: &Expr) -> T;
+    fn visit_for_in_stmt(&self, name: &Token, iterable: &Expr, body: &Stmt) -> T;
+    fn visit_print_stmt(&self, expr: &Expr) -> T;
+    fn visit_var_stmt(&self, token: &Token, expr: Option<&Expr>) -> T;
+}
+
+/// Walks the tree with mutable access to every node, for passes that rewrite
+/// it in place. Every method defaults to visiting the node's children, so a
+/// pass only overrides the nodes it cares about.
+pub trait VisitorMut {
+    fn handle_expr_mut(&mut self, expr: &mut Expr) {
+        walk_expr_mut(self, expr);
+    }
+
+    fn handle_stmt_mut(&mut self, stmt: &mut Stmt) {
+        match stmt {
+            Stmt::Block(statements) => self.visit_block_stmt_mut(statements),
+            Stmt::Expression(expr) => self.visit_expression_stmt_mut(expr),
+            Stmt::ForIn(name, iterable, body) => self.visit_for_in_stmt_mut(name, iterable, body),
+            Stmt::Print(expr) => self.visit_print_stmt_mut(expr),
+            Stmt::Var(token, expr) => self.visit_var_stmt_mut(token, expr.as_deref_mut()),
+        }
+    }
+
+    fn visit_assign_expr_mut(&mut self, _token: &mut Token, expr: &mut Expr) {
+        self.handle_expr_mut(expr);
+    }
+
+    fn visit_binary_expr_mut(&mut self, left: &mut Expr, _token: &mut Token, right: &mut Expr) {
+        self.handle_expr_mut(left);
+        self.handle_expr_mut(right);
+    }
+
+    fn visit_call_expr_mut(
+        &mut self,
+        callee: &mut Expr,
+        _paren: &mut Token,
+        arguments: &mut [Expr],
+    ) {
+        self.handle_expr_mut(callee);
+        for argument in arguments {
+            self.handle_expr_mut(argument);
         }
     }
 
-    fn visit_expression_stmt(&self, expr: Box<Expr>);
-    fn visit_print_stmt(&self, expr: Box<Expr>);
-    fn visit_var_stmt(&self, token: Token, expr: Option<Box<Expr>>);
+    fn visit_get_expr_mut(&mut self, object: &mut Expr, _name: &mut Token) {
+        self.handle_expr_mut(object);
+    }
+
+    fn visit_grouping_expr_mut(&mut self, expr: &mut Expr) {
+        self.handle_expr_mut(expr);
+    }
+
+    fn visit_index_expr_mut(&mut self, object: &mut Expr, _bracket: &mut Token, index: &mut Expr) {
+        self.handle_expr_mut(object);
+        self.handle_expr_mut(index);
+    }
+
+    fn visit_list_expr_mut(&mut self, items: &mut [Expr]) {
+        for item in items {
+            self.handle_expr_mut(item);
+        }
+    }
+
+    fn visit_literal_expr_mut(&mut self, _literal: &mut LiteralType) {}
+
+    fn visit_map_expr_mut(&mut self, _brace: &mut Token, entries: &mut [(Expr, Expr)]) {
+        for (key, value) in entries {
+            self.handle_expr_mut(key);
+            self.handle_expr_mut(value);
+        }
+    }
+
+    fn visit_set_expr_mut(&mut self, object: &mut Expr, _name: &mut Token, value: &mut Expr) {
+        self.handle_expr_mut(object);
+        self.handle_expr_mut(value);
+    }
+
+    fn visit_set_index_expr_mut(
+        &mut self,
+        object: &mut Expr,
+        _bracket: &mut Token,
+        index: &mut Expr,
+        value: &mut Expr,
+    ) {
+        self.handle_expr_mut(object);
+        self.handle_expr_mut(index);
+        self.handle_expr_mut(value);
+    }
+
+    fn visit_unary_expr_mut(&mut self, _token: &mut Token, expr: &mut Expr) {
+        self.handle_expr_mut(expr);
+    }
+
+    fn visit_variable_expr_mut(&mut self, _token: &mut Token) {}
+
+    fn visit_block_stmt_mut(&mut self, statements: &mut [Stmt]) {
+        for statement in statements {
+            self.handle_stmt_mut(statement);
+        }
+    }
+
+    fn visit_expression_stmt_mut(&mut self, expr: &mut Expr) {
+        self.handle_expr_mut(expr);
+    }
+
+    fn visit_for_in_stmt_mut(&mut self, _name: &mut Token, iterable: &mut Expr, body: &mut Stmt) {
+        self.handle_expr_mut(iterable);
+        self.handle_stmt_mut(body);
+    }
+
+    fn visit_print_stmt_mut(&mut self, expr: &mut Expr) {
+        self.handle_expr_mut(expr);
+    }
+
+    fn visit_var_stmt_mut(&mut self, _token: &mut Token, expr: Option<&mut Expr>) {
+        if let Some(initializer) = expr {
+            self.handle_expr_mut(initializer);
+        }
+    }
+}
+
+/// Dispatches `expr` to the matching `VisitorMut` method. Passes that override
+/// `handle_expr_mut` call this to keep walking, then look at the result.
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+    match expr {
+        Expr::Assign(token, expr) => visitor.visit_assign_expr_mut(token, expr),
+        Expr::Binary(left, token, right) => visitor.visit_binary_expr_mut(left, token, right),
+        Expr::Call(callee, paren, arguments) => {
+            visitor.visit_call_expr_mut(callee, paren, arguments)
+        }
+        Expr::Get(object, name) => visitor.visit_get_expr_mut(object, name),
+        Expr::Index(object, bracket, index) => visitor.visit_index_expr_mut(object, bracket, index),
+        Expr::List(items) => visitor.visit_list_expr_mut(items),
+        Expr::Literal(literal) => visitor.visit_literal_expr_mut(literal),
+        Expr::Map(brace, entries) => visitor.visit_map_expr_mut(brace, entries),
+        Expr::Grouping(expr) => visitor.visit_grouping_expr_mut(expr),
+        Expr::Set(object, name, value) => visitor.visit_set_expr_mut(object, name, value),
+        Expr::SetIndex(object, bracket, index, value) => {
+            visitor.visit_set_index_expr_mut(object, bracket, index, value)
+        }
+        Expr::Unary(token, expr) => visitor.visit_unary_expr_mut(token, expr),
+        Expr::Variable(token) => visitor.visit_variable_expr_mut(token),
+    }
 }
@@ -0,0 +1,53 @@
+//! JSON encoding of parsed programs, so tools written in other languages can
+//! consume Lox without reimplementing the parser.
+//!
+//! A program is an array of statements. Every `Stmt` and `Expr` node is an
+//! object with a `type` naming the variant and a `fields` array holding the
+//! variant's children in declaration order:
+//!
+//! ```json
+//! [
+//!   {
+//!     "type": "Print",
+//!     "fields": {
+//!       "type": "Binary",
+//!       "fields": [
+//!         { "type": "Literal", "fields": { "type": "LNumber", "value": 2.0 } },
+//!         { "token_type": "Plus", "lexeme": "+", "literal": null, "line": 1, "column": 9 },
+//!         { "type": "Variable", "fields": { "token_type": "Identifier", "lexeme": "a", "literal": null, "line": 1, "column": 11 } }
+//!       ]
+//!     }
+//!   }
+//! ]
+//! ```
+//!
+//! Variants with a single child (`Block`, `Expression`, `Print`, `Grouping`,
+//! `List`, `Literal`, `Variable`) hold it directly instead of in a one-element
+//! array.
+//!
+//! - Statements: `Block [stmt, ...]`, `Expression expr`,
+//!   `ForIn [token, expr, stmt]`, `Print expr`, `Var [token, expr | null]`.
+//! - Expressions: `Assign [token, expr]`, `Binary [expr, token, expr]`,
+//!   `Call [expr, token, [expr, ...]]` (the token is the closing paren),
+//!   `Get [expr, token]`, `Grouping expr`,
+//!   `Index [expr, token, expr]` (the token is the closing bracket),
+//!   `List [expr, ...]`, `Literal literal`,
+//!   `Map [token, [[key, value], ...]]` (the token is the closing brace),
+//!   `Set [expr, token, expr]`,
+//!   `SetIndex [expr, token, expr, expr]`, `Unary [token, expr]`,
+//!   `Variable token`.
+//! - Tokens are objects with `token_type` (the `TokenType` variant name, e.g.
+//!   `"GreaterEqual"`), `lexeme`, `literal` (a literal or `null`), `line` and
+//!   `column`. `column` may be left out when decoding and defaults to 0.
+//! - Literals are `{"type": "LNumber", "value": 1.5}`, `LString` with a string
+//!   value, `LBoolean` with a boolean value, or `{"type": "LNil"}`.
+
+use crate::ast::Stmt;
+
+pub fn to_json(statements: &[Stmt]) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(statements)
+}
+
+pub fn from_json(json: &str) -> serde_json::Result<Vec<Stmt>> {
+    serde_json::from_str(json)
+}
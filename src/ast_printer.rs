@@ -0,0 +1,145 @@
+use crate::{
+    ast::{Expr, Stmt, StmtVisitor, Visitor},
+    scanner::token::{LiteralType, Token},
+};
+
+/// Renders syntax trees as parenthesized S-expressions, e.g.
+/// `(* (- 123) (group 45.67))`. Useful to check how the parser grouped things.
+pub struct AstPrinter;
+
+impl AstPrinter {
+    pub fn new() -> Self {
+        AstPrinter
+    }
+
+    pub fn print(&self, stmt: &Stmt) -> String {
+        self.handle_stmt(stmt)
+    }
+
+    pub fn print_expr(&self, expr: &Expr) -> String {
+        self.handle_expr(expr)
+    }
+
+    fn parenthesize(&self, name: &str, exprs: &[&Expr]) -> String {
+        let mut text = format!("({}", name);
+        for expr in exprs {
+            text.push(' ');
+            text.push_str(&self.handle_expr(expr));
+        }
+        text.push(')');
+        text
+    }
+}
+
+impl Default for AstPrinter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Visitor<String> for AstPrinter {
+    fn visit_assign_expr(&self, token: &Token, expr: &Expr) -> String {
+        self.parenthesize(&format!("= {}", token.lexeme), &[expr])
+    }
+
+    fn visit_binary_expr(&self, left: &Expr, token: &Token, right: &Expr) -> String {
+        self.parenthesize(&token.lexeme, &[left, right])
+    }
+
+    fn visit_call_expr(&self, callee: &Expr, _paren: &Token, arguments: &[Expr]) -> String {
+        let mut exprs = vec![callee];
+        exprs.extend(arguments);
+        self.parenthesize("call", &exprs)
+    }
+
+    fn visit_get_expr(&self, object: &Expr, name: &Token) -> String {
+        self.parenthesize(&format!("get {}", name.lexeme), &[object])
+    }
+
+    fn visit_grouping_expr(&self, expr: &Expr) -> String {
+        self.parenthesize("group", &[expr])
+    }
+
+    fn visit_index_expr(&self, object: &Expr, _bracket: &Token, index: &Expr) -> String {
+        self.parenthesize("index", &[object, index])
+    }
+
+    fn visit_list_expr(&self, items: &[Expr]) -> String {
+        let items: Vec<&Expr> = items.iter().collect();
+        self.parenthesize("list", &items)
+    }
+
+    fn visit_literal_expr(&self, literal: &LiteralType) -> String {
+        match literal {
+            LiteralType::LString(value) => format!("\"{}\"", value),
+            _ => literal.stringify(),
+        }
+    }
+
+    fn visit_set_expr(&self, object: &Expr, name: &Token, value: &Expr) -> String {
+        self.parenthesize(&format!("set {}", name.lexeme), &[object, value])
+    }
+
+    fn visit_set_index_expr(
+        &self,
+        object: &Expr,
+        _bracket: &Token,
+        index: &Expr,
+        value: &Expr,
+    ) -> String {
+        self.parenthesize("set-index", &[object, index, value])
+    }
+
+    fn visit_map_expr(&self, _brace: &Token, entries: &[(Expr, Expr)]) -> String {
+        let entries: Vec<&Expr> = entries
+            .iter()
+            .flat_map(|(key, value)| [key, value])
+            .collect();
+        self.parenthesize("map", &entries)
+    }
+
+    fn visit_unary_expr(&self, token: &Token, expr: &Expr) -> String {
+        self.parenthesize(&token.lexeme, &[expr])
+    }
+
+    fn visit_variable_expr(&self, token: &Token) -> String {
+        token.lexeme.clone()
+    }
+}
+
+impl StmtVisitor<String> for AstPrinter {
+    fn visit_block_stmt(&self, statements: &[Stmt]) -> String {
+        let mut text = "(block".to_string();
+        for statement in statements {
+            text.push(' ');
+            text.push_str(&self.handle_stmt(statement));
+        }
+        text.push(')');
+        text
+    }
+
+    fn visit_expression_stmt(&self, expr: &Expr) -> String {
+        self.parenthesize(";", &[expr])
+    }
+
+    fn visit_for_in_stmt(&self, name: &Token, iterable: &Expr, body: &Stmt) -> String {
+        format!(
+            "(for-in {} {} {})",
+            name.lexeme,
+            self.handle_expr(iterable),
+            self.handle_stmt(body)
+        )
+    }
+
+    fn visit_print_stmt(&self, expr: &Expr) -> String {
+        self.parenthesize("print", &[expr])
+    }
+
+    fn visit_var_stmt(&self, token: &Token, expr: Option<&Expr>) -> String {
+        let name = format!("var {}", token.lexeme);
+        match expr {
+            Some(initializer) => self.parenthesize(&name, &[initializer]),
+            None => self.parenthesize(&name, &[]),
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! Runs `.lox` files written for the upstream Crafting Interpreters test suite
+//! against `rlox` and reports how many pass, grouped by book chapter.
+//!
+//! Usage: `conformance [--interpreter=PATH] [--verbose] DIR`
+//!
+//! Expectations are read from comments in each file, as in the upstream suite:
+//!
+//! - `// expect: text` expects `text` as the next line of output.
+//! - `// expect runtime error: message` expects `message` on stderr, followed
+//!   by `[line N]` for the line of the comment, and exit code 70.
+//! - `// Error at 'x': message` expects `[line N] Error at 'x': message` on
+//!   stderr for the line of the comment, and exit code 65.
+//! - `// [line N] Error...` and `// [java line N] Error...` expect a compile
+//!   error reported on line `N`. `// [c line N]` is clox-only and ignored.
+//!
+//! Files containing `// nontest` are skipped. The interpreter defaults to the
+//! `rlox` binary next to this one.
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    process::{self, Command},
+};
+
+const EXIT_COMPILE_ERROR: i32 = 65;
+const EXIT_RUNTIME_ERROR: i32 = 70;
+
+/// The chapter that introduces each top-level test directory (or root test
+/// file) of the upstream suite. Directories missing here are reported under
+/// their own name; `None` marks clox-only tests.
+fn chapter(name: &str) -> Option<String> {
+    let chapter = match name {
+        "scanning" => "04 Scanning",
+        "expressions" => "07 Evaluating Expressions",
+        "assignment"
+        | "block"
+        | "bool"
+        | "comments"
+        | "empty_file"
+        | "nil"
+        | "number"
+        | "operator"
+        | "precedence"
+        | "print"
+        | "string"
+        | "unexpected_character"
+        | "variable" => "08 Statements and State",
+        "if" | "logical_operator" | "while" | "for" => "09 Control Flow",
+        "call" | "function" | "return" => "10 Functions",
+        "closure" | "regression" => "11 Resolving and Binding",
+        "class" | "constructor" | "field" | "method" | "this" => "12 Classes",
+        "inheritance" | "super" => "13 Inheritance",
+        "benchmark" | "limit" => return None,
+        _ => return Some(name.to_string()),
+    };
+    Some(chapter.to_string())
+}
+
+#[derive(Default)]
+struct Expectations {
+    output: Vec<String>,
+    compile_errors: Vec<String>,
+    runtime_error: Option<(String, usize)>,
+}
+
+impl Expectations {
+    fn exit_code(&self) -> i32 {
+        if !self.compile_errors.is_empty() {
+            EXIT_COMPILE_ERROR
+        } else if self.runtime_error.is_some() {
+            EXIT_RUNTIME_ERROR
+        } else {
+            0
+        }
+    }
+}
+
+/// Returns `None` for files that aren't tests.
+fn parse_expectations(source: &str) -> Option<Expectations> {
+    let mut expectations = Expectations::default();
+
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let comment = match line.find("//") {
+            Some(start) => line[start + 2..].trim_start(),
+            None => continue,
+        };
+
+        if comment.starts_with("nontest") {
+            return None;
+        } else if let Some(text) = comment.strip_prefix("expect:") {
+            let text = text.strip_prefix(' ').unwrap_or(text);
+            expectations.output.push(text.to_string());
+        } else if let Some(message) = comment.strip_prefix("expect runtime error: ") {
+            expectations.runtime_error = Some((message.to_string(), line_number));
+        } else if comment.starts_with("Error") {
+            expectations
+                .compile_errors
+                .push(format!("[line {}] {}", line_number, comment));
+        } else if let Some(rest) = comment.strip_prefix('[') {
+            let rest = rest.strip_prefix("java ").unwrap_or(rest);
+            if rest.starts_with("line ") {
+                expectations.compile_errors.push(format!("[{}", rest));
+            }
+        }
+    }
+
+    Some(expectations)
+}
+
+/// Runs one test file, returning a description of every mismatch.
+fn run_test(interpreter: &Path, path: &Path, expectations: &Expectations) -> Vec<String> {
+    let output = match Command::new(interpreter).arg(path).output() {
+        Ok(output) => output,
+        Err(error) => {
+            return vec![format!(
+                "could not run {}: {}",
+                interpreter.display(),
+                error
+            )]
+        }
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let error_lines: Vec<&str> = stderr.lines().collect();
+    let mut failures = vec![];
+
+    if let Some((message, line)) = &expectations.runtime_error {
+        match error_lines.first() {
+            Some(first) if first == message => {
+                let trace = format!("[line {}]", line);
+                if error_lines.get(1) != Some(&trace.as_str()) {
+                    failures.push(format!("Expected '{}' after the runtime error.", trace));
+                }
+            }
+            Some(first) => failures.push(format!(
+                "Expected runtime error '{}' but got '{}'.",
+                message, first
+            )),
+            None => failures.push(format!(
+                "Expected runtime error '{}' but got none.",
+                message
+            )),
+        }
+    } else {
+        for expected in &expectations.compile_errors {
+            if !error_lines.contains(&expected.as_str()) {
+                failures.push(format!("Missing expected error: {}", expected));
+            }
+        }
+        for actual in &error_lines {
+            if !expectations.compile_errors.iter().any(|e| e == actual) {
+                failures.push(format!("Unexpected output on stderr: {}", actual));
+            }
+        }
+    }
+
+    let actual_output: Vec<&str> = stdout.lines().collect();
+    for (index, expected) in expectations.output.iter().enumerate() {
+        match actual_output.get(index) {
+            Some(actual) if actual == expected => (),
+            Some(actual) => failures.push(format!(
+                "Expected output '{}' on line {} and got '{}'.",
+                expected,
+                index + 1,
+                actual
+            )),
+            None => failures.push(format!("Missing expected output '{}'.", expected)),
+        }
+    }
+    for actual in actual_output.iter().skip(expectations.output.len()) {
+        failures.push(format!("Got output '{}' when none was expected.", actual));
+    }
+
+    let expected_code = expectations.exit_code();
+    let actual_code = output.status.code().unwrap_or(-1);
+    if actual_code != expected_code {
+        failures.push(format!(
+            "Expected exit code {} and got {}.",
+            expected_code, actual_code
+        ));
+    }
+
+    failures
+}
+
+fn collect_tests(dir: &Path, tests: &mut Vec<PathBuf>) {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap_or_else(|error| panic!("could not read {}: {}", dir.display(), error))
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            collect_tests(&path, tests);
+        } else if path.extension().is_some_and(|extension| extension == "lox") {
+            tests.push(path);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Tally {
+    passed: usize,
+    failed: usize,
+}
+
+fn main() {
+    let mut interpreter = env::current_exe()
+        .unwrap()
+        .with_file_name(format!("rlox{}", env::consts::EXE_SUFFIX));
+    let mut verbose = false;
+    let mut dir = None;
+
+    for arg in env::args().skip(1) {
+        if let Some(path) = arg.strip_prefix("--interpreter=") {
+            interpreter = PathBuf::from(path);
+        } else if arg == "--verbose" {
+            verbose = true;
+        } else if dir.is_none() && !arg.starts_with('-') {
+            dir = Some(PathBuf::from(arg));
+        } else {
+            println!("Usage: conformance [--interpreter=PATH] [--verbose] DIR");
+            process::exit(1);
+        }
+    }
+
+    let dir = match dir {
+        Some(dir) => dir,
+        None => {
+            println!("Usage: conformance [--interpreter=PATH] [--verbose] DIR");
+            process::exit(1);
+        }
+    };
+
+    let mut tests = vec![];
+    collect_tests(&dir, &mut tests);
+
+    let mut chapters: BTreeMap<String, Tally> = BTreeMap::new();
+    for path in tests {
+        let relative = path.strip_prefix(&dir).unwrap();
+        let first = relative.components().next().unwrap().as_os_str();
+        let name = first.to_string_lossy();
+        let chapter = match chapter(name.trim_end_matches(".lox")) {
+            Some(chapter) => chapter,
+            None => continue,
+        };
+
+        let source = fs::read_to_string(&path).unwrap();
+        let expectations = match parse_expectations(&source) {
+            Some(expectations) => expectations,
+            None => continue,
+        };
+
+        let failures = run_test(&interpreter, &path, &expectations);
+        let tally = chapters.entry(chapter).or_default();
+        if failures.is_empty() {
+            tally.passed += 1;
+            if verbose {
+                println!("PASS {}", relative.display());
+            }
+        } else {
+            tally.failed += 1;
+            println!("FAIL {}", relative.display());
+            for failure in failures {
+                println!("     {}", failure);
+            }
+        }
+    }
+
+    println!();
+    let mut total = Tally::default();
+    for (chapter, tally) in &chapters {
+        println!(
+            "{:<28} {:>4} passed {:>4} failed",
+            chapter, tally.passed, tally.failed
+        );
+        total.passed += tally.passed;
+        total.failed += tally.failed;
+    }
+    println!(
+        "{:<28} {:>4} passed {:>4} failed",
+        "Total", total.passed, total.failed
+    );
+
+    if total.failed > 0 {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,184 @@
+use std::path::{self, Component, Path, PathBuf};
+
+/// What the system natives may do on behalf of a script. Everything is denied
+/// by default; hosts grant only what a script needs.
+///
+/// ```
+/// use rlox::capabilities::Capabilities;
+///
+/// let capabilities = Capabilities::none().allow_read("data").allow_clock();
+/// assert!(capabilities.check_clock().is_ok());
+/// assert!(capabilities.check_env().is_err());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Capabilities {
+    pub read_roots: Vec<PathBuf>,
+    pub write_roots: Vec<PathBuf>,
+    pub clock: bool,
+    pub random: bool,
+    pub env: bool,
+    pub exit: bool,
+}
+
+impl Capabilities {
+    pub fn none() -> Self {
+        Capabilities::default()
+    }
+
+    /// Everything, with the whole filesystem readable and writable.
+    pub fn all() -> Self {
+        Capabilities {
+            read_roots: vec![PathBuf::from("/")],
+            write_roots: vec![PathBuf::from("/")],
+            clock: true,
+            random: true,
+            env: true,
+            exit: true,
+        }
+    }
+
+    /// Lets scripts read files anywhere under `root`.
+    pub fn allow_read<P: Into<PathBuf>>(mut self, root: P) -> Self {
+        self.read_roots.push(root.into());
+        self
+    }
+
+    /// Lets scripts create and overwrite files anywhere under `root`.
+    pub fn allow_write<P: Into<PathBuf>>(mut self, root: P) -> Self {
+        self.write_roots.push(root.into());
+        self
+    }
+
+    pub fn allow_clock(mut self) -> Self {
+        self.clock = true;
+        self
+    }
+
+    pub fn allow_random(mut self) -> Self {
+        self.random = true;
+        self
+    }
+
+    pub fn allow_env(mut self) -> Self {
+        self.env = true;
+        self
+    }
+
+    pub fn allow_exit(mut self) -> Self {
+        self.exit = true;
+        self
+    }
+
+    /// Resolves `path` and checks that it lies under a read root.
+    ///
+    /// Paths are checked as written before the filesystem is consulted, so a
+    /// script can't learn whether a file outside the roots exists.
+    pub fn check_read(&self, path: &str) -> Result<PathBuf, String> {
+        let denied = || format!("Reading '{}' is not allowed.", path);
+        if !lexically_within(Path::new(path), &self.read_roots) {
+            return Err(denied());
+        }
+
+        let resolved = Path::new(path)
+            .canonicalize()
+            .map_err(|error| format!("Could not open '{}': {}.", path, error))?;
+        if within(&resolved, &self.read_roots) {
+            Ok(resolved)
+        } else {
+            Err(denied())
+        }
+    }
+
+    /// Resolves `path`, which need not exist yet, and checks that it lies
+    /// under a write root. Like `check_read`, it checks the path as written
+    /// first.
+    pub fn check_write(&self, path: &str) -> Result<PathBuf, String> {
+        let path_buf = Path::new(path);
+        let denied = || format!("Writing '{}' is not allowed.", path);
+        if !lexically_within(path_buf, &self.write_roots) {
+            return Err(denied());
+        }
+
+        let file_name = path_buf.file_name().ok_or_else(denied)?;
+        let parent = match path_buf.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let mut resolved = parent
+            .canonicalize()
+            .map_err(|error| format!("Could not open '{}': {}.", path, error))?
+            .join(file_name);
+        // An existing file may be a symlink pointing out of the roots.
+        if let Ok(target) = resolved.canonicalize() {
+            resolved = target;
+        }
+
+        if within(&resolved, &self.write_roots) {
+            Ok(resolved)
+        } else {
+            Err(denied())
+        }
+    }
+
+    pub fn check_clock(&self) -> Result<(), String> {
+        check(self.clock, "clock")
+    }
+
+    pub fn check_random(&self) -> Result<(), String> {
+        check(self.random, "random")
+    }
+
+    pub fn check_env(&self) -> Result<(), String> {
+        check(self.env, "env")
+    }
+
+    pub fn check_exit(&self) -> Result<(), String> {
+        check(self.exit, "exit")
+    }
+}
+
+fn check(allowed: bool, capability: &str) -> Result<(), String> {
+    if allowed {
+        Ok(())
+    } else {
+        Err(format!("Script lacks the '{}' capability.", capability))
+    }
+}
+
+/// Whether `path` is inside any of `roots` going by the paths alone, with
+/// `.` and `..` resolved without following symlinks. Symlinks are caught
+/// afterwards by `within`.
+fn lexically_within(path: &Path, roots: &[PathBuf]) -> bool {
+    let path = match normalize(path) {
+        Some(path) => path,
+        None => return false,
+    };
+    roots
+        .iter()
+        .filter_map(|root| normalize(root))
+        .any(|root| path.starts_with(root))
+}
+
+/// `path` made absolute, with `.` and `..` components folded away.
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path::absolute(path).ok()?.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    Some(normalized)
+}
+
+/// Whether `path`, already canonical, is inside any of `roots`. Roots that
+/// don't exist contain nothing.
+fn within(path: &Path, roots: &[PathBuf]) -> bool {
+    roots
+        .iter()
+        .filter_map(|root| root.canonicalize().ok())
+        .any(|root| path.starts_with(root))
+}
@@ -1,39 +1,59 @@
-use std::{cell::RefCell, collections::HashMap};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 use crate::interpreter::LoxValue;
 
+/// One scope of variables. Lookups and assignments that miss fall through to
+/// the enclosing scope; the globals have none.
 pub struct Environment {
     values: RefCell<HashMap<String, LoxValue>>,
+    enclosing: Option<Rc<Environment>>,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Environment {
     pub fn new() -> Self {
         Environment {
             values: RefCell::new(HashMap::new()),
+            enclosing: None,
         }
     }
 
-    pub fn define(&self, name: String, value: Option<LoxValue>) {
-        if let Some(val) = value {
-            self.values.borrow_mut().insert(name, val);
+    /// A new scope nested inside `enclosing`.
+    pub fn with_enclosing(enclosing: Rc<Environment>) -> Self {
+        Environment {
+            values: RefCell::new(HashMap::new()),
+            enclosing: Some(enclosing),
         }
     }
 
+    pub fn define(&self, name: String, value: LoxValue) {
+        self.values.borrow_mut().insert(name, value);
+    }
+
     pub fn get(&self, name: &str) -> Option<LoxValue> {
-        let values = self.values.borrow();
-        return match values.get(name) {
-            Some(val) => Some(val.clone()),
-            _ => panic!("Undefined variable {}.", name),
-        };
+        match self.values.borrow().get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.enclosing.as_ref()?.get(name),
+        }
     }
 
-    pub fn assign(&self, name: String, value: &LoxValue) {
+    /// Returns `false` if `name` was never defined.
+    pub fn assign(&self, name: String, value: &LoxValue) -> bool {
         let mut values = self.values.borrow_mut();
-        match values.get(&name) {
-            Some(_) => {
-                values.insert(name, value.clone());
+        match values.get_mut(&name) {
+            Some(slot) => {
+                *slot = value.clone();
+                true
             }
-            _ => panic!("Undefined variable {}.", name),
+            None => match &self.enclosing {
+                Some(enclosing) => enclosing.assign(name, value),
+                None => false,
+            },
         }
     }
 }
@@ -1,21 +1,116 @@
+use std::fmt;
+
+#[derive(Debug)]
 pub enum Error {
     ScannerError(usize, String),
     ParseError(usize, String),
+    OptimizerError(usize, String),
+    StrictError(usize, String),
+    RuntimeError(usize, String),
+    IoError(String),
+    ConversionError(String),
+    /// An error raised inside calls, with the calls that were in progress,
+    /// outermost first.
+    Traced(Box<Error>, Vec<Frame>),
+    /// The script called `exit(code)`.
+    Exit(i32),
+    OutOfFuel,
+    /// The script allocated more than `Interpreter::set_allocation_limit`
+    /// allows.
+    OutOfMemory,
+    Cancelled,
+}
+
+/// A call in progress: the function called and where it was called from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub function: String,
+    pub line: usize,
+    pub column: usize,
 }
 
 use Error::*;
 
 impl Error {
+    /// The calls in progress when the error happened, outermost first.
+    pub fn trace(&self) -> &[Frame] {
+        match self {
+            Traced(_, frames) => frames,
+            _ => &[],
+        }
+    }
+
+    /// The error as it passes out of a call made at `frame`, which becomes
+    /// the outermost frame of its trace. Errors without a line, such as
+    /// `Exit` or the limits, pass through untraced.
+    pub(crate) fn called_from(self, frame: Frame) -> Error {
+        match self {
+            Traced(error, mut frames) => {
+                frames.insert(0, frame);
+                Traced(error, frames)
+            }
+            error @ (ScannerError(..) | ParseError(..) | OptimizerError(..) | StrictError(..)
+            | RuntimeError(..)) => Traced(Box::new(error), vec![frame]),
+            error => error,
+        }
+    }
+
+    /// The error itself, without its trace.
+    pub fn cause(&self) -> &Error {
+        match self {
+            Traced(error, _) => error,
+            error => error,
+        }
+    }
+
     pub fn report(&self) {
+        eprintln!("{}", self);
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match &self {
             ScannerError(line, message) => {
-                eprintln!("[line {}], ScannerError: {}", line, message);
+                write!(f, "[line {}], ScannerError: {}", line, message)
             }
             ParseError(line, message) => {
-                eprintln!("[line {}], ParseError: {}", line, message);
+                write!(f, "[line {}], ParseError: {}", line, message)
+            }
+            OptimizerError(line, message) => {
+                write!(f, "[line {}], OptimizerError: {}", line, message)
+            }
+            StrictError(line, message) => {
+                write!(f, "[line {}], StrictError: {}", line, message)
             }
+            RuntimeError(line, message) => {
+                write!(f, "[line {}], RuntimeError: {}", line, message)
+            }
+            IoError(message) => {
+                write!(f, "IoError: {}", message)
+            }
+            ConversionError(message) => {
+                write!(f, "ConversionError: {}", message)
+            }
+            Traced(error, frames) => {
+                writeln!(f, "Traceback (most recent call last):")?;
+                for frame in frames {
+                    writeln!(
+                        f,
+                        "  [line {}, column {}] in {}()",
+                        frame.line, frame.column, frame.function
+                    )?;
+                }
+                write!(f, "{}", error)
+            }
+            Exit(code) => write!(f, "Exit: Script exited with code {}.", code),
+            OutOfFuel => write!(f, "OutOfFuel: Script exceeded its execution budget."),
+            OutOfMemory => write!(f, "OutOfMemory: Script exceeded its allocation limit."),
+            Cancelled => write!(f, "Cancelled: Script was cancelled by the host."),
         }
     }
 }
 
+impl std::error::Error for Error {}
+
 pub type LoxResult<T> = Result<T, Error>;
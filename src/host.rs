@@ -0,0 +1,132 @@
+use std::{cell::RefCell, collections::HashMap, fmt};
+
+use crate::{interpreter::LoxValue, native::NativeResult};
+
+/// A Rust value handed to scripts as an opaque object. Scripts read its
+/// properties with `object.name`, write them with `object.name = value` and
+/// call its methods with `object.name(args)`; each goes through the matching
+/// hook here. Errors are reported as runtime errors at the script location;
+/// methods can also pass on an `Error` as natives do (see `NativeError`).
+///
+/// Objects are shared behind an `Rc`, so implementations that support `set`
+/// need interior mutability.
+///
+/// `for (item in object)` loops over an object whose `iter` method returns an
+/// iterator object: the loop calls the iterator's `next` method for each item
+/// and stops when it returns `nil`.
+pub trait HostObject {
+    /// Shown when the object is printed, as `<type_name> instance`.
+    fn type_name(&self) -> &str;
+
+    /// Returns `None` if there is no such property.
+    fn get(&self, _name: &str) -> Option<LoxValue> {
+        None
+    }
+
+    fn set(&self, _name: &str, _value: LoxValue) -> Result<(), String> {
+        Err(format!("Can't set properties on {}.", self.type_name()))
+    }
+
+    /// Method calls on host objects always come here, even when `get` knows a
+    /// property with the same name.
+    fn call_method(&self, name: &str, _arguments: &[LoxValue]) -> NativeResult {
+        Err(format!("Undefined method '{}'.", name).into())
+    }
+
+    /// The object's fields in order, for hosts that want it to be
+    /// serializable. `None` means it is opaque.
+    fn fields(&self) -> Option<Vec<(String, LoxValue)>> {
+        None
+    }
+}
+
+/// A plain bag of named fields. Rust structs and maps converted with
+/// `serde_value::to_value` arrive in scripts as records; scripts can read,
+/// change and add fields.
+pub struct Record {
+    type_name: String,
+    fields: RefCell<Vec<(String, LoxValue)>>,
+}
+
+impl Record {
+    pub fn new(type_name: &str, fields: Vec<(String, LoxValue)>) -> Self {
+        Record {
+            type_name: type_name.to_string(),
+            fields: RefCell::new(fields),
+        }
+    }
+}
+
+impl HostObject for Record {
+    fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    fn get(&self, name: &str) -> Option<LoxValue> {
+        self.fields
+            .borrow()
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.clone())
+    }
+
+    fn set(&self, name: &str, value: LoxValue) -> Result<(), String> {
+        let mut fields = self.fields.borrow_mut();
+        match fields.iter_mut().find(|(field, _)| field == name) {
+            Some((_, slot)) => *slot = value,
+            None => fields.push((name.to_string(), value)),
+        }
+        Ok(())
+    }
+
+    fn fields(&self) -> Option<Vec<(String, LoxValue)>> {
+        Some(self.fields.borrow().clone())
+    }
+}
+
+/// A fixed set of named values, such as a library of natives. Scripts can
+/// read members and call the functions among them, but not assign to them.
+pub struct Namespace {
+    name: String,
+    members: HashMap<String, LoxValue>,
+}
+
+impl Namespace {
+    pub fn new(name: &str, members: Vec<(String, LoxValue)>) -> Self {
+        Namespace {
+            name: name.to_string(),
+            members: members.into_iter().collect(),
+        }
+    }
+}
+
+impl HostObject for Namespace {
+    fn type_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get(&self, name: &str) -> Option<LoxValue> {
+        self.members.get(name).cloned()
+    }
+
+    fn set(&self, name: &str, _value: LoxValue) -> Result<(), String> {
+        Err(format!("Can't assign to '{}.{}'.", self.name, name))
+    }
+
+    fn call_method(&self, name: &str, arguments: &[LoxValue]) -> NativeResult {
+        match self.members.get(name) {
+            Some(LoxValue::NativeFunction(function)) => {
+                function.check_arity(arguments.len())?;
+                function.call(arguments)
+            }
+            Some(_) => Err("Can only call functions and classes.".into()),
+            None => Err(format!("Undefined property '{}'.", name).into()),
+        }
+    }
+}
+
+impl fmt::Debug for dyn HostObject {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{} instance>", self.type_name())
+    }
+}
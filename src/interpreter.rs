@@ -1,129 +1,988 @@
+use std::{
+    cell::{Cell, RefCell},
+    io::{self, BufRead, Read, Write},
+    mem, ptr,
+    rc::Rc,
+    slice,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
 use crate::{
+    allocations::Allocations,
     ast::{Expr, Stmt, StmtVisitor, Visitor},
+    capabilities::Capabilities,
     environment::Environment,
+    errors::{Error, Frame, LoxResult},
+    host::HostObject,
+    lists,
+    map::{self, LoxMap, MapKey},
+    math,
+    native::{NativeError, NativeFunction, NativeResult},
+    parser::DEFAULT_MAX_NESTING_DEPTH,
+    prelude,
     scanner::{
         token::{LiteralType, Token},
         tokenType::TokenType,
     },
+    strings, system,
 };
 
-#[derive(Clone)]
-pub struct LoxValue {
-    value: LiteralType,
+/// A runtime value. Literals compare by value; everything else compares by
+/// identity.
+#[derive(Clone, Debug)]
+pub enum LoxValue {
+    Literal(LiteralType),
+    NativeFunction(Rc<NativeFunction>),
+    HostObject(Rc<dyn HostObject>),
+    /// A mutable list, shared by everyone holding it.
+    List(Rc<RefCell<Vec<LoxValue>>>),
+    /// A mutable map, shared like a list.
+    Map(Rc<RefCell<LoxMap>>),
+}
+
+impl PartialEq for LoxValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (LoxValue::Literal(left), LoxValue::Literal(right)) => left == right,
+            (LoxValue::NativeFunction(left), LoxValue::NativeFunction(right)) => {
+                Rc::ptr_eq(left, right)
+            }
+            (LoxValue::HostObject(left), LoxValue::HostObject(right)) => {
+                ptr::addr_eq(Rc::as_ptr(left), Rc::as_ptr(right))
+            }
+            (LoxValue::List(left), LoxValue::List(right)) => Rc::ptr_eq(left, right),
+            (LoxValue::Map(left), LoxValue::Map(right)) => Rc::ptr_eq(left, right),
+            _ => false,
+        }
+    }
+}
+
+impl LoxValue {
+    pub fn nil() -> Self {
+        LoxValue::Literal(LiteralType::LNil)
+    }
+
+    pub fn host_object<T: HostObject + 'static>(object: T) -> Self {
+        LoxValue::HostObject(Rc::new(object))
+    }
+
+    pub fn list(items: Vec<LoxValue>) -> Self {
+        LoxValue::List(Rc::new(RefCell::new(items)))
+    }
+
+    pub fn map(map: LoxMap) -> Self {
+        LoxValue::Map(Rc::new(RefCell::new(map)))
+    }
+
+    pub fn is_nil(&self) -> bool {
+        matches!(self, LoxValue::Literal(LiteralType::LNil))
+    }
+
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            LoxValue::Literal(literal) => literal.is_truthy(),
+            _ => true,
+        }
+    }
+
+    pub fn stringify(&self) -> String {
+        self.stringify_within(&mut vec![])
+    }
+
+    /// `stringify` for a value inside the lists and maps in `printing`. A
+    /// list or map that contains itself shows up as `[...]` or `{...}` where
+    /// it recurs.
+    fn stringify_within(&self, printing: &mut Vec<*const ()>) -> String {
+        match self {
+            LoxValue::Literal(literal) => literal.stringify(),
+            LoxValue::NativeFunction(_) => "<native fn>".to_string(),
+            LoxValue::HostObject(object) => format!("{} instance", object.type_name()),
+            LoxValue::List(list) => {
+                let id = Rc::as_ptr(list) as *const ();
+                if printing.contains(&id) {
+                    return "[...]".to_string();
+                }
+                printing.push(id);
+                let items: Vec<String> = list
+                    .borrow()
+                    .iter()
+                    .map(|item| item.stringify_within(printing))
+                    .collect();
+                printing.pop();
+                format!("[{}]", items.join(", "))
+            }
+            LoxValue::Map(map) => {
+                let id = Rc::as_ptr(map) as *const ();
+                if printing.contains(&id) {
+                    return "{...}".to_string();
+                }
+                printing.push(id);
+                let entries: Vec<String> = map
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| {
+                        format!("{}: {}", key.quoted(), value.stringify_within(printing))
+                    })
+                    .collect();
+                printing.pop();
+                format!("{{{}}}", entries.join(", "))
+            }
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            LoxValue::Literal(LiteralType::LString(_)) => "string",
+            LoxValue::Literal(LiteralType::LNumber(_)) => "number",
+            LoxValue::Literal(LiteralType::LBoolean(_)) => "boolean",
+            LoxValue::Literal(LiteralType::LNil) => "nil",
+            LoxValue::NativeFunction(_) => "function",
+            LoxValue::HostObject(_) => "object",
+            LoxValue::List(_) => "list",
+            LoxValue::Map(_) => "map",
+        }
+    }
+
+    /// Applies a binary operator. Only literals support arithmetic and
+    /// comparison; any two values can be tested for equality.
+    pub fn apply(self, operator: TokenType, other: LoxValue) -> Result<Self, String> {
+        match (self, other) {
+            (LoxValue::Literal(left), LoxValue::Literal(right)) => {
+                left.apply(operator, right).map(LoxValue::Literal)
+            }
+            (left, right) => match operator {
+                TokenType::EqualEqual => Ok(LoxValue::from(left == right)),
+                TokenType::BangEqual => Ok(LoxValue::from(left != right)),
+                TokenType::Plus => Err("Operands must be two numbers or two strings.".to_string()),
+                _ => Err("Operands must be numbers.".to_string()),
+            },
+        }
+    }
+
+    pub fn apply_unary(self, operator: TokenType) -> Result<Self, String> {
+        match self {
+            LoxValue::Literal(literal) => literal.apply_unary(operator).map(LoxValue::Literal),
+            value => match operator {
+                TokenType::Bang => Ok(LoxValue::from(!value.is_truthy())),
+                _ => Err("Operand must be a number.".to_string()),
+            },
+        }
+    }
+
+    fn conversion_error(&self, expected: &str) -> Error {
+        Error::ConversionError(format!(
+            "Expected a {} but got {}.",
+            expected,
+            self.type_name()
+        ))
+    }
+}
+
+impl From<f64> for LoxValue {
+    fn from(value: f64) -> Self {
+        LoxValue::Literal(LiteralType::LNumber(value))
+    }
+}
+
+impl From<bool> for LoxValue {
+    fn from(value: bool) -> Self {
+        LoxValue::Literal(LiteralType::LBoolean(value))
+    }
+}
+
+impl From<String> for LoxValue {
+    fn from(value: String) -> Self {
+        LoxValue::Literal(LiteralType::LString(value))
+    }
+}
+
+impl From<&str> for LoxValue {
+    fn from(value: &str) -> Self {
+        LoxValue::from(value.to_string())
+    }
+}
+
+/// `None` becomes `nil`.
+impl<T: Into<LoxValue>> From<Option<T>> for LoxValue {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => value.into(),
+            None => LoxValue::nil(),
+        }
+    }
+}
+
+impl TryFrom<LoxValue> for f64 {
+    type Error = Error;
+
+    fn try_from(value: LoxValue) -> Result<Self, Self::Error> {
+        match value {
+            LoxValue::Literal(LiteralType::LNumber(number)) => Ok(number),
+            _ => Err(value.conversion_error("number")),
+        }
+    }
+}
+
+impl TryFrom<LoxValue> for bool {
+    type Error = Error;
+
+    fn try_from(value: LoxValue) -> Result<Self, Self::Error> {
+        match value {
+            LoxValue::Literal(LiteralType::LBoolean(boolean)) => Ok(boolean),
+            _ => Err(value.conversion_error("boolean")),
+        }
+    }
+}
+
+impl TryFrom<LoxValue> for String {
+    type Error = Error;
+
+    fn try_from(value: LoxValue) -> Result<Self, Self::Error> {
+        match value {
+            LoxValue::Literal(LiteralType::LString(string)) => Ok(string),
+            _ => Err(value.conversion_error("string")),
+        }
+    }
+}
+
+/// `nil` becomes `None`. A blanket impl over `Option<T>` would overlap with
+/// the standard library's, so each type gets its own.
+macro_rules! impl_try_from_for_option {
+    ($($target:ty),*) => {
+        $(
+            impl TryFrom<LoxValue> for Option<$target> {
+                type Error = Error;
+
+                fn try_from(value: LoxValue) -> Result<Self, Self::Error> {
+                    if value.is_nil() {
+                        return Ok(None);
+                    }
+                    <$target>::try_from(value).map(Some)
+                }
+            }
+        )*
+    };
 }
 
+impl_try_from_for_option!(f64, bool, String);
+
+/// How many steps run between checks of the cancellation flag.
+const CANCEL_CHECK_INTERVAL: u64 = 1024;
+
+/// How deeply calls may nest unless the host says otherwise. Kept well below
+/// what the Rust stack can take, since every Lox call is many Rust frames.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 256;
+
 pub struct Interpreter {
-    environment: Environment,
+    globals: Rc<Environment>,
+    /// The innermost scope of the code being run.
+    environment: RefCell<Rc<Environment>>,
+    output: RefCell<Box<dyn Write>>,
+    input: Rc<RefCell<Box<dyn BufRead>>>,
+    steps: Cell<u64>,
+    fuel: Cell<Option<u64>>,
+    cancel: RefCell<Option<Arc<AtomicBool>>>,
+    call_depth: Cell<usize>,
+    max_call_depth: Cell<Option<usize>>,
+    nesting_depth: Cell<usize>,
+    max_nesting_depth: Cell<Option<usize>>,
+    allocations: Rc<Allocations>,
+    capabilities: Rc<RefCell<Capabilities>>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
+    /// An interpreter that prints to stdout and reads from stdin.
     pub fn new() -> Interpreter {
-        Interpreter {
-            environment: Environment::new(),
-        }
+        Interpreter::with_io(Box::new(io::stdout()), Box::new(SharedStdin::default()))
+    }
+
+    /// An interpreter that prints to `output` and reads from `input`, for
+    /// embedders that want to capture or redirect what scripts do.
+    pub fn with_io(output: Box<dyn Write>, input: Box<dyn BufRead>) -> Interpreter {
+        let globals = Rc::new(Environment::new());
+        let interpreter = Interpreter {
+            environment: RefCell::new(Rc::clone(&globals)),
+            globals,
+            output: RefCell::new(output),
+            input: Rc::new(RefCell::new(input)),
+            steps: Cell::new(0),
+            fuel: Cell::new(None),
+            cancel: RefCell::new(None),
+            call_depth: Cell::new(0),
+            max_call_depth: Cell::new(Some(DEFAULT_MAX_CALL_DEPTH)),
+            nesting_depth: Cell::new(0),
+            max_nesting_depth: Cell::new(Some(DEFAULT_MAX_NESTING_DEPTH)),
+            allocations: Rc::new(Allocations::default()),
+            capabilities: Rc::new(RefCell::new(Capabilities::none())),
+        };
+        prelude::define(
+            &interpreter,
+            &interpreter.input,
+            &interpreter.capabilities,
+            &interpreter.allocations,
+        );
+        system::define(
+            &interpreter,
+            &interpreter.capabilities,
+            &interpreter.allocations,
+        );
+        interpreter
+            .globals
+            .define("math".to_string(), LoxValue::host_object(math::namespace()));
+        interpreter
     }
 
-    pub fn interpret(&self, statements: Vec<Stmt>) {
+    pub fn interpret(&self, statements: &[Stmt]) -> LoxResult<()> {
+        self.check_cancelled()?;
         for statement in statements {
-            self.execute(statement);
+            self.execute(statement)?;
+        }
+
+        Ok(())
+    }
+
+    /// Exposes a Rust function to scripts as the global `name`. Calls with a
+    /// different number of arguments than `arity` fail before reaching it.
+    /// See `NativeError` for how it can fail.
+    ///
+    /// ```
+    /// use rlox::interpreter::{Interpreter, LoxValue};
+    ///
+    /// let interpreter = Interpreter::new();
+    /// interpreter.define_native("double", 1, |args| match f64::try_from(args[0].clone()) {
+    ///     Ok(number) => Ok(LoxValue::from(number * 2.0)),
+    ///     Err(_) => Err("Argument must be a number.".into()),
+    /// });
+    /// ```
+    pub fn define_native<F>(&self, name: &str, arity: usize, function: F)
+    where
+        F: Fn(&[LoxValue]) -> NativeResult + 'static,
+    {
+        let native = NativeFunction::new(name, arity, function);
+        self.globals
+            .define(name.to_string(), LoxValue::NativeFunction(Rc::new(native)));
+    }
+
+    /// Limits how many more steps scripts may take before failing with
+    /// `Error::OutOfFuel`, or lifts the limit with `None`. Every statement
+    /// executed and every expression evaluated is one step. The budget is
+    /// shared by all later runs until it is set again.
+    pub fn set_fuel(&self, fuel: Option<u64>) {
+        self.fuel.set(fuel);
+    }
+
+    /// The steps left in the budget, or `None` if there is no limit.
+    pub fn fuel(&self) -> Option<u64> {
+        self.fuel.get()
+    }
+
+    /// Makes scripts fail with `Error::Cancelled` once `flag` is set, e.g.
+    /// from a watchdog thread. The flag is checked every thousand or so steps,
+    /// so a script may run a little longer after it is set.
+    ///
+    /// ```
+    /// use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+    /// use rlox::{errors::Error, lox::Lox};
+    ///
+    /// let lox = Lox::new();
+    /// let cancel = Arc::new(AtomicBool::new(false));
+    /// lox.interpreter().set_cancel_flag(cancel.clone());
+    ///
+    /// cancel.store(true, Ordering::Relaxed);
+    /// assert!(matches!(lox.eval("1 + 2;"), Err(Error::Cancelled)));
+    /// ```
+    pub fn set_cancel_flag(&self, flag: Arc<AtomicBool>) {
+        *self.cancel.borrow_mut() = Some(flag);
+    }
+
+    /// Limits how deeply calls may nest before failing with a "Stack
+    /// overflow." runtime error. Defaults to `DEFAULT_MAX_CALL_DEPTH`;
+    /// `None` removes the limit, leaving only the Rust stack.
+    pub fn set_max_call_depth(&self, depth: Option<usize>) {
+        self.max_call_depth.set(depth);
+    }
+
+    /// Limits how deeply statements and expressions may nest, both when
+    /// `Lox` parses a script and when the interpreter runs it, before failing
+    /// with a parse error or a "Stack overflow." runtime error. Defaults to
+    /// `DEFAULT_MAX_NESTING_DEPTH`; `None` removes the limit, leaving only the
+    /// Rust stack.
+    pub fn set_max_nesting_depth(&self, depth: Option<usize>) {
+        self.max_nesting_depth.set(depth);
+    }
+
+    pub fn max_nesting_depth(&self) -> Option<usize> {
+        self.max_nesting_depth.get()
+    }
+
+    /// Limits roughly how many bytes of strings, lists and maps scripts may
+    /// allocate in total before failing with `Error::OutOfMemory`, or lifts
+    /// the limit with `None`.
+    ///
+    /// This is a budget for allocations, not a cap on live memory: nothing is
+    /// credited back when values are freed, so a loop that keeps building
+    /// short-lived strings uses it up just like one that keeps them all.
+    /// Values returned by natives the host defines aren't charged, since the
+    /// host built them.
+    pub fn set_allocation_limit(&self, bytes: Option<usize>) {
+        self.allocations.set_limit(bytes);
+    }
+
+    /// Roughly how many bytes scripts have allocated so far, including
+    /// values that have since been freed.
+    pub fn allocated(&self) -> usize {
+        self.allocations.allocated()
+    }
+
+    /// Replaces what the system natives (`clock`, `readFile` and so on) are
+    /// allowed to do. Scripts start with no capabilities at all.
+    pub fn set_capabilities(&self, capabilities: Capabilities) {
+        *self.capabilities.borrow_mut() = capabilities;
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities.borrow().clone()
+    }
+
+    /// The global variables scripts see.
+    pub fn globals(&self) -> &Environment {
+        &self.globals
+    }
+
+    /// Reads one line from the interpreter's input, without the line ending.
+    /// Returns `None` at end of input.
+    pub fn read_line(&self) -> LoxResult<Option<String>> {
+        read_line(&self.input)
+    }
+
+    /// Runs `statements` in `environment`, then goes back to the current
+    /// scope whether or not they succeeded.
+    fn execute_block(&self, statements: &[Stmt], environment: Environment) -> LoxResult<()> {
+        let previous = self.environment.replace(Rc::new(environment));
+        let result = statements
+            .iter()
+            .try_for_each(|statement| self.execute(statement));
+        *self.environment.borrow_mut() = previous;
+        result
+    }
+
+    /// Runs one pass of a `for`-`in` loop, with `name` bound to `item` in a
+    /// fresh scope.
+    fn execute_loop_body(&self, name: &Token, item: LoxValue, body: &Stmt) -> LoxResult<()> {
+        let environment = Environment::with_enclosing(Rc::clone(&self.environment.borrow()));
+        environment.define(name.lexeme.clone(), item);
+        self.execute_block(slice::from_ref(body), environment)
+    }
+
+    fn execute(&self, stmt: &Stmt) -> LoxResult<()> {
+        self.step()?;
+        self.nested(|| stmt.line(), || self.handle_stmt(stmt))
+    }
+
+    pub fn evaluate(&self, expr: &Expr) -> LoxResult<LoxValue> {
+        self.step()?;
+        self.nested(|| expr.line(), || self.handle_expr(expr))
+    }
+
+    /// Runs `run` one level deeper, failing at the line `line` finds if that
+    /// is past the maximum nesting depth.
+    fn nested<T, L, F>(&self, line: L, run: F) -> LoxResult<T>
+    where
+        L: FnOnce() -> Option<usize>,
+        F: FnOnce() -> LoxResult<T>,
+    {
+        let depth = self.nesting_depth.get();
+        if self.max_nesting_depth.get().is_some_and(|max| depth >= max) {
+            return Err(Error::RuntimeError(
+                line().unwrap_or_default(),
+                "Stack overflow.".to_string(),
+            ));
+        }
+
+        self.nesting_depth.set(depth + 1);
+        let result = run();
+        self.nesting_depth.set(depth);
+        result
+    }
+
+    /// Charges one step against the fuel budget and periodically checks for
+    /// cancellation.
+    fn step(&self) -> LoxResult<()> {
+        if let Some(fuel) = self.fuel.get() {
+            if fuel == 0 {
+                return Err(Error::OutOfFuel);
+            }
+            self.fuel.set(Some(fuel - 1));
+        }
+
+        let steps = self.steps.get() + 1;
+        self.steps.set(steps);
+        if steps.is_multiple_of(CANCEL_CHECK_INTERVAL) {
+            self.check_cancelled()?;
+        }
+
+        Ok(())
+    }
+
+    /// Runs a native function or host method one call deeper.
+    ///
+    /// A native that fails with a message is reported at `paren`, its call
+    /// site, so its own frame isn't in the trace. An `Error` it passes on
+    /// gets a frame for this call, pointing at `site` where the callee is
+    /// named, so the trace builds up as the error unwinds.
+    fn call<F>(&self, function: String, site: &Token, paren: &Token, call: F) -> LoxResult<LoxValue>
+    where
+        F: FnOnce() -> NativeResult,
+    {
+        let depth = self.call_depth.get();
+        if self.max_call_depth.get().is_some_and(|max| depth >= max) {
+            return Err(Error::RuntimeError(
+                paren.line,
+                "Stack overflow.".to_string(),
+            ));
+        }
+
+        self.call_depth.set(depth + 1);
+        let result = call();
+        self.call_depth.set(depth);
+
+        result.map_err(|error| match error {
+            NativeError::Message(message) => Error::RuntimeError(paren.line, message),
+            NativeError::Error(error) => error.called_from(Frame {
+                function,
+                line: site.line,
+                column: site.column,
+            }),
+        })
+    }
+
+    fn undefined_variable(&self, token: &Token) -> Error {
+        Error::RuntimeError(
+            token.line,
+            format!("Undefined variable '{}'.", token.lexeme),
+        )
+    }
+
+    fn check_cancelled(&self) -> LoxResult<()> {
+        match &*self.cancel.borrow() {
+            Some(flag) if flag.load(Ordering::Relaxed) => Err(Error::Cancelled),
+            _ => Ok(()),
         }
     }
 
-    fn execute(&self, stmt: Stmt) {
-        self.handle_stmt(stmt);
+    fn evaluate_arguments(&self, arguments: &[Expr]) -> LoxResult<Vec<LoxValue>> {
+        arguments
+            .iter()
+            .map(|argument| self.evaluate(argument))
+            .collect()
     }
 
-    fn evaluate(&self, expr: &Expr) -> LoxValue {
-        self.handle_expr(expr.clone())
+    fn list_index(&self, index: &LoxValue, bracket: &Token) -> LoxResult<usize> {
+        match index {
+            LoxValue::Literal(LiteralType::LNumber(number))
+                if number.fract() == 0.0 && *number >= 0.0 =>
+            {
+                Ok(*number as usize)
+            }
+            _ => Err(Error::RuntimeError(
+                bracket.line,
+                "Index must be a non-negative integer.".to_string(),
+            )),
+        }
     }
 
-    fn is_truthy(&self, object: Option<LoxValue>) -> bool {
+    fn map_key(&self, key: LoxValue, bracket: &Token) -> LoxResult<MapKey> {
+        MapKey::try_from(key).map_err(|message| Error::RuntimeError(bracket.line, message))
+    }
+
+    fn get_property(&self, object: &LoxValue, name: &Token) -> LoxResult<LoxValue> {
         match object {
-            None => false,
-            Some(lox_value) => match lox_value.value {
-                LiteralType::LBoolean(literal) => literal,
-                _ => true,
-            },
+            LoxValue::Literal(LiteralType::LString(string)) => {
+                strings::method(string, &name.lexeme, &self.allocations).ok_or_else(|| {
+                    Error::RuntimeError(name.line, format!("Undefined property '{}'.", name.lexeme))
+                })
+            }
+            LoxValue::List(list) => lists::method(list, &name.lexeme, &self.allocations)
+                .ok_or_else(|| {
+                    Error::RuntimeError(name.line, format!("Undefined property '{}'.", name.lexeme))
+                }),
+            LoxValue::Map(map) => {
+                map::method(map, &name.lexeme, &self.allocations).ok_or_else(|| {
+                    Error::RuntimeError(name.line, format!("Undefined property '{}'.", name.lexeme))
+                })
+            }
+            LoxValue::HostObject(host) => host.get(&name.lexeme).ok_or_else(|| {
+                Error::RuntimeError(name.line, format!("Undefined property '{}'.", name.lexeme))
+            }),
+            _ => Err(Error::RuntimeError(
+                name.line,
+                "Only instances have properties.".to_string(),
+            )),
         }
     }
 }
 
-impl Visitor<LoxValue> for Interpreter {
-    fn visit_literal_expr(&self, literal: LiteralType) -> LoxValue {
-        LoxValue { value: literal }
+impl Visitor<LoxResult<LoxValue>> for Interpreter {
+    fn visit_literal_expr(&self, literal: &LiteralType) -> LoxResult<LoxValue> {
+        Ok(LoxValue::Literal(literal.clone()))
+    }
+
+    fn visit_grouping_expr(&self, expr: &Expr) -> LoxResult<LoxValue> {
+        self.evaluate(expr)
+    }
+
+    fn visit_unary_expr(&self, token: &Token, expr: &Expr) -> LoxResult<LoxValue> {
+        let right = self.evaluate(expr)?;
+
+        right
+            .apply_unary(token.token_type)
+            .map_err(|message| Error::RuntimeError(token.line, message))
     }
 
-    fn visit_grouping_expr(&self, expr: Box<Expr>) -> LoxValue {
-        self.evaluate(expr.as_ref())
+    fn visit_binary_expr(&self, left: &Expr, token: &Token, right: &Expr) -> LoxResult<LoxValue> {
+        let i_left = self.evaluate(left)?;
+        let i_right = self.evaluate(right)?;
+
+        let value = i_left
+            .apply(token.token_type, i_right)
+            .map_err(|message| Error::RuntimeError(token.line, message))?;
+        self.allocations.charged(value)
     }
 
-    fn visit_unary_expr(&self, token: Token, expr: Box<Expr>) -> LoxValue {
-        let right = self.evaluate(expr.as_ref());
-        let mut value = LiteralType::LNil;
-        let mut inner_value = 0f64;
-        if let LiteralType::LNumber(val) = right.value {
-            inner_value = val;
+    fn visit_call_expr(
+        &self,
+        callee: &Expr,
+        paren: &Token,
+        arguments: &[Expr],
+    ) -> LoxResult<LoxValue> {
+        let site = match callee {
+            Expr::Variable(name) | Expr::Get(_, name) => name,
+            _ => paren,
         };
 
-        if let Expr::Unary(_, _) = *expr {
-            match token.token_type {
-                TokenType::Minus => value = LiteralType::LNumber(inner_value * -1_f64),
-                TokenType::Bang => value = LiteralType::LBoolean(self.is_truthy(Some(right))),
-                _ => (),
+        let callee = match callee {
+            Expr::Get(object, name) => {
+                let object = self.evaluate(object)?;
+                if let LoxValue::HostObject(host) = &object {
+                    let values = self.evaluate_arguments(arguments)?;
+                    let method = format!("{}.{}", host.type_name(), name.lexeme);
+                    return self.call(method, name, paren, || {
+                        host.call_method(&name.lexeme, &values)
+                    });
+                }
+                self.get_property(&object, name)?
             }
-        }
+            _ => self.evaluate(callee)?,
+        };
 
-        LoxValue { value }
+        let values = self.evaluate_arguments(arguments)?;
+
+        let function = match callee {
+            LoxValue::NativeFunction(function) => function,
+            _ => {
+                return Err(Error::RuntimeError(
+                    paren.line,
+                    "Can only call functions and classes.".to_string(),
+                ))
+            }
+        };
+
+        function
+            .check_arity(values.len())
+            .map_err(|message| Error::RuntimeError(paren.line, message))?;
+
+        self.call(function.name.clone(), site, paren, || {
+            function.call(&values)
+        })
     }
 
-    fn visit_binary_expr(&self, left: Box<Expr>, token: Token, right: Box<Expr>) -> LoxValue {
-        let i_left = self.evaluate(left.as_ref());
-        let i_right = self.evaluate(right.as_ref());
+    fn visit_get_expr(&self, object: &Expr, name: &Token) -> LoxResult<LoxValue> {
+        let object = self.evaluate(object)?;
+        self.get_property(&object, name)
+    }
 
-        let value = match token.token_type {
-            TokenType::Greater => i_left.value.greater(i_right.value),
-            TokenType::GreaterEqual => i_left.value.greater_equal(i_right.value),
-            TokenType::Less => i_left.value.less(i_right.value),
-            TokenType::LessEqual => i_left.value.less_equal(i_right.value),
-            TokenType::EqualEqual => i_left.value.equal(i_right.value),
-            TokenType::BangEqual => i_left.value.not_equal(i_right.value),
-            TokenType::Minus => i_left.value - i_right.value,
-            TokenType::Slash => i_left.value / i_right.value,
-            TokenType::Star => i_left.value * i_right.value,
-            TokenType::Plus => i_left.value + i_right.value,
-            _ => panic!("invalid operator"),
+    fn visit_set_expr(&self, object: &Expr, name: &Token, value: &Expr) -> LoxResult<LoxValue> {
+        let host = match self.evaluate(object)? {
+            LoxValue::HostObject(host) => host,
+            _ => {
+                return Err(Error::RuntimeError(
+                    name.line,
+                    "Only instances have fields.".to_string(),
+                ))
+            }
         };
 
-        LoxValue { value }
+        let value = self.evaluate(value)?;
+        host.set(&name.lexeme, value.clone())
+            .map_err(|message| Error::RuntimeError(name.line, message))?;
+        Ok(value)
+    }
+
+    fn visit_list_expr(&self, items: &[Expr]) -> LoxResult<LoxValue> {
+        let list = LoxValue::list(self.evaluate_arguments(items)?);
+        self.allocations.charged(list)
+    }
+
+    fn visit_map_expr(&self, brace: &Token, entries: &[(Expr, Expr)]) -> LoxResult<LoxValue> {
+        let mut map = LoxMap::new();
+        for (key, value) in entries {
+            let key = self.evaluate(key)?;
+            let key = self.map_key(key, brace)?;
+            map.insert(key, self.evaluate(value)?);
+        }
+
+        self.allocations.charged(LoxValue::map(map))
+    }
+
+    fn visit_index_expr(
+        &self,
+        object: &Expr,
+        bracket: &Token,
+        index: &Expr,
+    ) -> LoxResult<LoxValue> {
+        let object = self.evaluate(object)?;
+        let index = self.evaluate(index)?;
+
+        let item =
+            match &object {
+                LoxValue::List(list) => {
+                    let position = self.list_index(&index, bracket)?;
+                    list.borrow().get(position).cloned()
+                }
+                LoxValue::Literal(LiteralType::LString(string)) => {
+                    let position = self.list_index(&index, bracket)?;
+                    string
+                        .chars()
+                        .nth(position)
+                        .map(|c| LoxValue::from(c.to_string()))
+                }
+                LoxValue::Map(map) => {
+                    let key = self.map_key(index, bracket)?;
+                    return map.borrow().get(&key).cloned().ok_or_else(|| {
+                        Error::RuntimeError(bracket.line, map::undefined_key(&key))
+                    });
+                }
+                _ => {
+                    return Err(Error::RuntimeError(
+                        bracket.line,
+                        "Only lists, maps and strings can be indexed.".to_string(),
+                    ))
+                }
+            };
+        item.ok_or_else(|| Error::RuntimeError(bracket.line, "Index out of bounds.".to_string()))
+    }
+
+    fn visit_set_index_expr(
+        &self,
+        object: &Expr,
+        bracket: &Token,
+        index: &Expr,
+        value: &Expr,
+    ) -> LoxResult<LoxValue> {
+        match self.evaluate(object)? {
+            LoxValue::List(list) => {
+                let index = self.evaluate(index)?;
+                let position = self.list_index(&index, bracket)?;
+                let value = self.evaluate(value)?;
+
+                match list.borrow_mut().get_mut(position) {
+                    Some(item) => *item = value.clone(),
+                    None => {
+                        return Err(Error::RuntimeError(
+                            bracket.line,
+                            "Index out of bounds.".to_string(),
+                        ))
+                    }
+                }
+                Ok(value)
+            }
+            LoxValue::Map(map) => {
+                let key = self.evaluate(index)?;
+                let key = self.map_key(key, bracket)?;
+                let value = self.evaluate(value)?;
+                if !map.borrow().contains_key(&key) {
+                    self.allocations
+                        .charge(mem::size_of::<MapKey>() + mem::size_of::<LoxValue>())?;
+                }
+                map.borrow_mut().insert(key, value.clone());
+                Ok(value)
+            }
+            _ => Err(Error::RuntimeError(
+                bracket.line,
+                "Only list and map items can be assigned.".to_string(),
+            )),
+        }
     }
 
-    fn visit_variable_expr(&self, token: Token) -> LoxValue {
-        self.environment.get(&token.lexeme).unwrap()
+    fn visit_variable_expr(&self, token: &Token) -> LoxResult<LoxValue> {
+        self.environment
+            .borrow()
+            .get(&token.lexeme)
+            .ok_or_else(|| self.undefined_variable(token))
     }
 
-    fn visit_assign_expr(&self, token: Token, expr: Box<Expr>) -> LoxValue {
-        let value = self.evaluate(&expr);
-        self.environment.assign(token.lexeme, &value);
-        value
+    fn visit_assign_expr(&self, token: &Token, expr: &Expr) -> LoxResult<LoxValue> {
+        let value = self.evaluate(expr)?;
+        if !self
+            .environment
+            .borrow()
+            .assign(token.lexeme.clone(), &value)
+        {
+            return Err(self.undefined_variable(token));
+        }
+        Ok(value)
     }
 }
 
-impl StmtVisitor for Interpreter {
-    fn visit_print_stmt(&self, expr: Box<Expr>) {
-        let value = self.evaluate(&expr);
-        println!("{}", value.value.stringify());
+impl StmtVisitor<LoxResult<()>> for Interpreter {
+    fn visit_print_stmt(&self, expr: &Expr) -> LoxResult<()> {
+        let value = self.evaluate(expr)?;
+        writeln!(self.output.borrow_mut(), "{}", value.stringify())
+            .map_err(|error| Error::IoError(error.to_string()))
+    }
+
+    fn visit_expression_stmt(&self, expr: &Expr) -> LoxResult<()> {
+        self.evaluate(expr)?;
+        Ok(())
+    }
+
+    fn visit_var_stmt(&self, token: &Token, expr: Option<&Expr>) -> LoxResult<()> {
+        let value = match expr {
+            Some(var) => self.evaluate(var)?,
+            None => LoxValue::nil(),
+        };
+
+        self.environment
+            .borrow()
+            .define(token.lexeme.clone(), value);
+        Ok(())
+    }
+
+    fn visit_block_stmt(&self, statements: &[Stmt]) -> LoxResult<()> {
+        let environment = Environment::with_enclosing(Rc::clone(&self.environment.borrow()));
+        self.execute_block(statements, environment)
+    }
+
+    fn visit_for_in_stmt(&self, name: &Token, iterable: &Expr, body: &Stmt) -> LoxResult<()> {
+        match self.evaluate(iterable)? {
+            // The list is read afresh each time round, so the body may change
+            // it; items it appends are visited too.
+            LoxValue::List(list) => {
+                let mut position = 0;
+                loop {
+                    let item = list.borrow().get(position).cloned();
+                    match item {
+                        Some(item) => self.execute_loop_body(name, item, body)?,
+                        None => return Ok(()),
+                    }
+                    position += 1;
+                }
+            }
+            LoxValue::Map(map) => {
+                let keys: Vec<LoxValue> = map
+                    .borrow()
+                    .iter()
+                    .map(|(key, _)| LoxValue::from(key.clone()))
+                    .collect();
+                for key in keys {
+                    self.execute_loop_body(name, key, body)?;
+                }
+                Ok(())
+            }
+            LoxValue::Literal(LiteralType::LString(string)) => {
+                for c in string.chars() {
+                    self.execute_loop_body(name, LoxValue::from(c.to_string()), body)?;
+                }
+                Ok(())
+            }
+            LoxValue::HostObject(host) => {
+                let method = format!("{}.iter", host.type_name());
+                let iterator =
+                    match self.call(method, name, name, || host.call_method("iter", &[]))? {
+                        LoxValue::HostObject(iterator) => iterator,
+                        other => {
+                            return Err(Error::RuntimeError(
+                                name.line,
+                                format!(
+                                    "iter() must return an object but got {}.",
+                                    other.type_name()
+                                ),
+                            ))
+                        }
+                    };
+
+                let method = format!("{}.next", iterator.type_name());
+                loop {
+                    let item = self.call(method.clone(), name, name, || {
+                        iterator.call_method("next", &[])
+                    })?;
+                    if item.is_nil() {
+                        return Ok(());
+                    }
+                    self.execute_loop_body(name, item, body)?;
+                }
+            }
+            _ => Err(Error::RuntimeError(
+                name.line,
+                "Can only iterate over lists, maps, strings and iterable objects.".to_string(),
+            )),
+        }
     }
+}
+
+/// Reads stdin a line at a time, holding the lock only while reading, so
+/// several interpreters and the REPL can share it.
+#[derive(Default)]
+struct SharedStdin {
+    line: Vec<u8>,
+    position: usize,
+}
 
-    fn visit_expression_stmt(&self, expr: Box<Expr>) {
-        self.evaluate(&expr);
+impl Read for SharedStdin {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let read = available.len().min(buf.len());
+        buf[..read].copy_from_slice(&available[..read]);
+        self.consume(read);
+        Ok(read)
     }
+}
 
-    fn visit_var_stmt(&self, token: Token, expr: Option<Box<Expr>>) {
-        let mut value: Option<LoxValue> = None;
-        if let Some(var) = expr {
-            value = Some(self.evaluate(&var));
+impl BufRead for SharedStdin {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.position >= self.line.len() {
+            self.line.clear();
+            self.position = 0;
+            io::stdin().lock().read_until(b'\n', &mut self.line)?;
         }
+        Ok(&self.line[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position += amount;
+    }
+}
 
-        self.environment.define(token.lexeme, value);
+pub(crate) fn read_line(input: &RefCell<Box<dyn BufRead>>) -> LoxResult<Option<String>> {
+    let mut line = String::new();
+    let read = input
+        .borrow_mut()
+        .read_line(&mut line)
+        .map_err(|error| Error::IoError(error.to_string()))?;
+    if read == 0 {
+        return Ok(None);
     }
+
+    let trimmed = line.trim_end_matches(['\n', '\r']).len();
+    line.truncate(trimmed);
+    Ok(Some(line))
 }
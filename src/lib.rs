@@ -1,6 +1,25 @@
+mod allocations;
 pub mod ast;
+#[cfg(feature = "serde")]
+pub mod ast_json;
+pub mod ast_printer;
+pub mod capabilities;
 pub mod environment;
 pub mod errors;
+pub mod host;
 pub mod interpreter;
+mod lists;
+pub mod lox;
+pub mod map;
+mod math;
+pub mod native;
+pub mod optimizer;
 pub mod parser;
+mod prelude;
+mod range;
 pub mod scanner;
+#[cfg(feature = "serde")]
+pub mod serde_value;
+pub mod strict;
+mod strings;
+mod system;
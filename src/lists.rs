@@ -0,0 +1,164 @@
+//! Methods on list values, e.g. `xs.push(1)`. Lists are shared, so every
+//! method that changes a list changes it for everyone holding it.
+//!
+//! - `push(item)` appends `item` and returns the new length.
+//! - `pop()` removes and returns the last item.
+//! - `insert(index, item)` puts `item` before the item at `index`; an index
+//!   equal to the length appends.
+//! - `remove(index)` removes and returns the item at `index`.
+//! - `len()` is the number of items.
+//! - `slice(start, end)` is a new list of the items from `start` up to but not
+//!   including `end`.
+//! - `map(f)`, `filter(f)` and `reduce(f, initial)` build new values from
+//!   the items by calling `f`, which must be a function.
+//! - `sort()` sorts the list in place; its items must be all numbers or all
+//!   strings.
+
+use std::{cell::RefCell, cmp::Ordering, mem, rc::Rc, slice};
+
+use crate::{
+    allocations::Allocations,
+    interpreter::LoxValue,
+    native::{NativeFunction, NativeResult},
+    scanner::token::LiteralType,
+    strings::{expected, index},
+};
+
+type List = Rc<RefCell<Vec<LoxValue>>>;
+
+/// The method `name` of `list`, bound to it, or `None` if lists have no such
+/// method. Methods that grow the list charge each new item to `allocations`,
+/// and methods that build a new list charge all of it.
+pub(crate) fn method(list: &List, name: &str, allocations: &Rc<Allocations>) -> Option<LoxValue> {
+    let arity = match name {
+        "pop" | "len" | "sort" => 0,
+        "push" | "remove" | "map" | "filter" => 1,
+        "insert" | "slice" | "reduce" => 2,
+        _ => return None,
+    };
+
+    let list = Rc::clone(list);
+    let method = name.to_string();
+    let allocations = Rc::clone(allocations);
+    let native = NativeFunction::new(name, arity, move |args| {
+        call(&list, &method, args, &allocations)
+    });
+    Some(LoxValue::NativeFunction(Rc::new(native)))
+}
+
+fn call(list: &List, method: &str, args: &[LoxValue], allocations: &Allocations) -> NativeResult {
+    let value = match method {
+        "push" => {
+            allocations.charge(mem::size_of::<LoxValue>())?;
+            let mut items = list.borrow_mut();
+            items.push(args[0].clone());
+            LoxValue::from(items.len() as f64)
+        }
+        "pop" => match list.borrow_mut().pop() {
+            Some(item) => item,
+            None => return Err("Can't pop from an empty list.".into()),
+        },
+        "insert" => {
+            let position = index(method, &args[0])?;
+            let mut items = list.borrow_mut();
+            if position > items.len() {
+                return Err("Index out of bounds.".into());
+            }
+            allocations.charge(mem::size_of::<LoxValue>())?;
+            items.insert(position, args[1].clone());
+            LoxValue::nil()
+        }
+        "remove" => {
+            let position = index(method, &args[0])?;
+            let mut items = list.borrow_mut();
+            if position >= items.len() {
+                return Err("Index out of bounds.".into());
+            }
+            items.remove(position)
+        }
+        "len" => LoxValue::from(list.borrow().len() as f64),
+        "slice" => {
+            let start = index(method, &args[0])?;
+            let end = index(method, &args[1])?;
+            let items = list.borrow();
+            if start > end || end > items.len() {
+                return Err("Slice range out of bounds.".into());
+            }
+            allocations.charged(LoxValue::list(items[start..end].to_vec()))?
+        }
+        // The callbacks get a copy of the items, so they are free to change
+        // the list while it is being walked.
+        "map" => {
+            let function = callback(method, &args[0], 1)?;
+            let items = list.borrow().clone();
+            let mapped = items
+                .into_iter()
+                .map(|item| function.call(&[item]))
+                .collect::<Result<Vec<_>, _>>()?;
+            allocations.charged(LoxValue::list(mapped))?
+        }
+        "filter" => {
+            let function = callback(method, &args[0], 1)?;
+            let items = list.borrow().clone();
+            let mut kept = vec![];
+            for item in items {
+                if function.call(slice::from_ref(&item))?.is_truthy() {
+                    kept.push(item);
+                }
+            }
+            allocations.charged(LoxValue::list(kept))?
+        }
+        "reduce" => {
+            let function = callback(method, &args[0], 2)?;
+            let items = list.borrow().clone();
+            let mut accumulator = args[1].clone();
+            for item in items {
+                accumulator = function.call(&[accumulator, item])?;
+            }
+            accumulator
+        }
+        "sort" => {
+            let mut items = list.borrow().clone();
+            let all_numbers = items
+                .iter()
+                .all(|item| matches!(item, LoxValue::Literal(LiteralType::LNumber(_))));
+            let all_strings = items
+                .iter()
+                .all(|item| matches!(item, LoxValue::Literal(LiteralType::LString(_))));
+            if !all_numbers && !all_strings {
+                return Err("sort() expects a list of only numbers or only strings.".into());
+            }
+            items.sort_by(compare);
+            *list.borrow_mut() = items;
+            LoxValue::nil()
+        }
+        _ => unreachable!("no list method '{}'", method),
+    };
+
+    Ok(value)
+}
+
+/// A function argument taking `arity` arguments.
+fn callback(method: &str, value: &LoxValue, arity: usize) -> Result<Rc<NativeFunction>, String> {
+    match value {
+        LoxValue::NativeFunction(function) => {
+            function.check_arity(arity)?;
+            Ok(Rc::clone(function))
+        }
+        other => Err(expected(method, "a function", other)),
+    }
+}
+
+fn compare(left: &LoxValue, right: &LoxValue) -> Ordering {
+    match (left, right) {
+        (
+            LoxValue::Literal(LiteralType::LNumber(left)),
+            LoxValue::Literal(LiteralType::LNumber(right)),
+        ) => left.total_cmp(right),
+        (
+            LoxValue::Literal(LiteralType::LString(left)),
+            LoxValue::Literal(LiteralType::LString(right)),
+        ) => left.cmp(right),
+        _ => Ordering::Equal,
+    }
+}
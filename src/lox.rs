@@ -0,0 +1,92 @@
+use std::{fs, path::Path};
+
+use crate::{
+    ast::Stmt,
+    errors::{Error, LoxResult},
+    interpreter::{Interpreter, LoxValue},
+    native::NativeResult,
+    parser::Parser,
+    scanner::Scanner,
+};
+
+/// Everything needed to run Lox from a Rust program. Globals persist between
+/// calls, so values set from Rust are visible to later scripts and vice versa.
+///
+/// ```
+/// use rlox::lox::Lox;
+///
+/// let lox = Lox::new();
+/// lox.set_global("width", 3.0);
+/// let area: f64 = lox.eval("var height = 4; width * height;")?.try_into()?;
+/// assert_eq!(area, 12.0);
+/// # Ok::<(), rlox::errors::Error>(())
+/// ```
+pub struct Lox {
+    interpreter: Interpreter,
+}
+
+impl Lox {
+    pub fn new() -> Self {
+        Lox::with_interpreter(Interpreter::new())
+    }
+
+    /// Wraps an interpreter configured by the caller, e.g. one built with
+    /// `Interpreter::with_io`.
+    pub fn with_interpreter(interpreter: Interpreter) -> Self {
+        Lox { interpreter }
+    }
+
+    pub fn interpreter(&self) -> &Interpreter {
+        &self.interpreter
+    }
+
+    /// Runs `source` and returns the value of its last statement if that is an
+    /// expression statement, or `nil` otherwise.
+    pub fn eval(&self, source: &str) -> LoxResult<LoxValue> {
+        let statements = Parser::new(Scanner::new(source.to_string()))
+            .with_max_depth(self.interpreter.max_nesting_depth())
+            .parse()?;
+
+        match statements.split_last() {
+            Some((Stmt::Expression(expr), rest)) => {
+                self.interpreter.interpret(rest)?;
+                self.interpreter.evaluate(expr)
+            }
+            _ => {
+                self.interpreter.interpret(&statements)?;
+                Ok(LoxValue::nil())
+            }
+        }
+    }
+
+    pub fn run_file<P: AsRef<Path>>(&self, path: P) -> LoxResult<()> {
+        let source = fs::read_to_string(path).map_err(|error| Error::IoError(error.to_string()))?;
+        self.eval(&source)?;
+        Ok(())
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<LoxValue> {
+        self.interpreter.globals().get(name)
+    }
+
+    /// See `Interpreter::define_native`.
+    pub fn define_native<F>(&self, name: &str, arity: usize, function: F)
+    where
+        F: Fn(&[LoxValue]) -> NativeResult + 'static,
+    {
+        self.interpreter.define_native(name, arity, function);
+    }
+
+    /// Defines a global, replacing any existing one with the same name.
+    pub fn set_global<V: Into<LoxValue>>(&self, name: &str, value: V) {
+        self.interpreter
+            .globals()
+            .define(name.to_string(), value.into());
+    }
+}
+
+impl Default for Lox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
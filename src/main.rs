@@ -3,58 +3,149 @@ use std::fs;
 use std::io::{self, Write};
 use std::process;
 
-use rlox::errors::LoxResult;
+use rlox::ast_printer::AstPrinter;
+use rlox::capabilities::Capabilities;
+use rlox::errors::{Error, LoxResult};
 use rlox::interpreter::Interpreter;
+use rlox::optimizer::Optimizer;
 use rlox::parser::Parser;
 
 use rlox::scanner::Scanner;
+use rlox::strict::StrictChecker;
 
-fn run(source: String) -> LoxResult<()> {
-    let mut scanner: Scanner = Scanner::new(source);
+/// What to do with a parsed program.
+#[derive(Clone, Copy)]
+enum Emit {
+    Run,
+    Ast,
+    AstJson,
+}
+
+#[derive(Clone)]
+struct Options {
+    emit: Emit,
+    optimize: bool,
+    strict: bool,
+    capabilities: Capabilities,
+}
 
-    scanner.scan_tokens()?;
+fn run(source: String, options: &Options) -> LoxResult<()> {
+    let scanner: Scanner = Scanner::new(source);
+    let mut parser = Parser::new(scanner);
 
-    let tokens = scanner.tokens;
-    let mut parser = Parser::new(tokens);
+    let mut statements = parser.parse()?;
 
-    let statements = parser.parse()?;
-    let interpreter = Interpreter::new();
+    if options.strict {
+        StrictChecker::new().check(&statements)?;
+    }
 
-    interpreter.interpret(statements);
+    if options.optimize {
+        Optimizer::new().optimize(&mut statements)?;
+    }
+
+    match options.emit {
+        Emit::Run => {
+            let interpreter = Interpreter::new();
+            interpreter.set_capabilities(options.capabilities.clone());
+            interpreter.interpret(&statements)?;
+        }
+        Emit::Ast => {
+            let printer = AstPrinter::new();
+            for statement in &statements {
+                println!("{}", printer.print(statement));
+            }
+        }
+        Emit::AstJson => emit_ast_json(&statements)?,
+    }
 
     Ok(())
 }
 
-fn run_file(path: &str) {
+#[cfg(feature = "serde")]
+fn emit_ast_json(statements: &[rlox::ast::Stmt]) -> LoxResult<()> {
+    let json = rlox::ast_json::to_json(statements)
+        .map_err(|error| Error::ConversionError(error.to_string()))?;
+    println!("{}", json);
+    Ok(())
+}
+
+#[cfg(not(feature = "serde"))]
+fn emit_ast_json(_statements: &[rlox::ast::Stmt]) -> LoxResult<()> {
+    eprintln!("--emit=ast-json requires rlox to be built with the `serde` feature");
+    process::exit(1);
+}
+
+fn run_file(path: &str, options: &Options) {
     let content = fs::read_to_string(path).expect("File not found");
-    let result = run(content);
-    if let Err(error) = result {
-        error.report();
-        process::exit(1);
+    match run(content, options) {
+        Ok(()) => (),
+        Err(Error::Exit(code)) => process::exit(code),
+        Err(error) => {
+            error.report();
+            process::exit(1);
+        }
     }
 }
 
-fn run_prompt() {
+fn run_prompt(options: &Options) {
     loop {
         let mut line = String::new();
         print!("> ");
         let _ = io::stdout().flush();
         io::stdin().read_line(&mut line).unwrap();
-        if let Err(error) = run(line) {
-            error.report();
+        match run(line, options) {
+            Ok(()) => (),
+            Err(Error::Exit(code)) => process::exit(code),
+            Err(error) => error.report(),
         }
     }
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let (flags, args): (Vec<String>, Vec<String>) =
+        env::args().skip(1).partition(|arg| arg.starts_with('-'));
+
+    let mut options = Options {
+        emit: Emit::Run,
+        optimize: false,
+        strict: false,
+        capabilities: Capabilities::none(),
+    };
+    for flag in flags {
+        let capabilities = options.capabilities.clone();
+        match flag.as_str() {
+            "-O" => options.optimize = true,
+            "--strict" => options.strict = true,
+            "--dump-ast" => options.emit = Emit::Ast,
+            "--emit=ast-json" => options.emit = Emit::AstJson,
+            "--allow-all" => options.capabilities = Capabilities::all(),
+            "--allow-clock" => options.capabilities = capabilities.allow_clock(),
+            "--allow-random" => options.capabilities = capabilities.allow_random(),
+            "--allow-env" => options.capabilities = capabilities.allow_env(),
+            "--allow-exit" => options.capabilities = capabilities.allow_exit(),
+            _ if flag.starts_with("--allow-read=") => {
+                options.capabilities = capabilities.allow_read(&flag["--allow-read=".len()..])
+            }
+            _ if flag.starts_with("--allow-write=") => {
+                options.capabilities = capabilities.allow_write(&flag["--allow-write=".len()..])
+            }
+            _ => {
+                println!("Unknown option: {}", flag);
+                process::exit(1);
+            }
+        }
+    }
 
-    if args.len() > 2 {
-        println!("Usage: rlox [script]");
+    if args.len() > 1 {
+        println!(
+            "Usage: rlox [-O] [--strict] [--dump-ast | --emit=ast-json] [--allow-read=DIR] \
+             [--allow-write=DIR] [--allow-clock] [--allow-random] [--allow-env] [--allow-exit] \
+             [--allow-all] [script]"
+        );
         process::exit(1);
-    } else if args.len() == 2 {
-        run_file(&args[1]);
+    } else if args.len() == 1 {
+        run_file(&args[0], &options);
     } else {
-        run_prompt();
+        run_prompt(&options);
     }
 }
@@ -0,0 +1,209 @@
+//! Maps from hashable values to values, e.g. `{"a": 1, "b": 2}`. Keys can be
+//! strings, numbers, booleans or nil; entries keep the order they were first
+//! inserted in. Maps print like their literals, with string keys quoted.
+//!
+//! Methods on map values:
+//!
+//! - `keys()` and `values()` are lists of the keys and values.
+//! - `has(key)` is whether `key` is in the map.
+//! - `remove(key)` removes `key` and returns its value.
+//! - `len()` is the number of entries.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+use crate::{
+    allocations::Allocations,
+    interpreter::LoxValue,
+    native::{NativeFunction, NativeResult},
+    scanner::token::LiteralType,
+};
+
+/// A value usable as a map key. Numbers hash by value, so `0` and `-0` are
+/// the same key; NaN isn't equal to itself and can't be a key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapKey(LiteralType);
+
+impl MapKey {
+    pub fn stringify(&self) -> String {
+        self.0.stringify()
+    }
+
+    /// The key as it appears in a printed map. Strings are quoted, so the
+    /// keys `"1"` and `1` look different.
+    pub fn quoted(&self) -> String {
+        match &self.0 {
+            LiteralType::LString(string) => format!("\"{}\"", string),
+            literal => literal.stringify(),
+        }
+    }
+}
+
+impl Eq for MapKey {}
+
+impl Hash for MapKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match &self.0 {
+            LiteralType::LString(string) => (0u8, string).hash(state),
+            LiteralType::LNumber(number) => (1u8, number.to_bits()).hash(state),
+            LiteralType::LBoolean(boolean) => (2u8, boolean).hash(state),
+            LiteralType::LNil => 3u8.hash(state),
+        }
+    }
+}
+
+impl TryFrom<LoxValue> for MapKey {
+    type Error = String;
+
+    fn try_from(value: LoxValue) -> Result<Self, Self::Error> {
+        match value {
+            LoxValue::Literal(LiteralType::LNumber(number)) if number.is_nan() => {
+                Err("NaN can't be a map key.".to_string())
+            }
+            LoxValue::Literal(LiteralType::LNumber(0.0)) => Ok(MapKey(LiteralType::LNumber(0.0))),
+            LoxValue::Literal(literal) => Ok(MapKey(literal)),
+            other => Err(format!(
+                "Map keys must be strings, numbers, booleans or nil, not {}.",
+                other.type_name()
+            )),
+        }
+    }
+}
+
+impl From<MapKey> for LoxValue {
+    fn from(key: MapKey) -> Self {
+        LoxValue::Literal(key.0)
+    }
+}
+
+/// The entries of a map value, in insertion order.
+#[derive(Clone, Debug, Default)]
+pub struct LoxMap {
+    entries: Vec<(MapKey, LoxValue)>,
+    positions: HashMap<MapKey, usize>,
+}
+
+impl LoxMap {
+    pub fn new() -> Self {
+        LoxMap::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &MapKey) -> Option<&LoxValue> {
+        self.positions
+            .get(key)
+            .map(|&position| &self.entries[position].1)
+    }
+
+    pub fn contains_key(&self, key: &MapKey) -> bool {
+        self.positions.contains_key(key)
+    }
+
+    /// Sets `key` to `value`, returning the old value. A new key goes at the
+    /// end; an existing one keeps its place.
+    pub fn insert(&mut self, key: MapKey, value: LoxValue) -> Option<LoxValue> {
+        match self.positions.get(&key) {
+            Some(&position) => Some(std::mem::replace(&mut self.entries[position].1, value)),
+            None => {
+                self.positions.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &MapKey) -> Option<LoxValue> {
+        let position = self.positions.remove(key)?;
+        let (_, value) = self.entries.remove(position);
+        for (key, _) in &self.entries[position..] {
+            *self.positions.get_mut(key).unwrap() -= 1;
+        }
+        Some(value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&MapKey, &LoxValue)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+}
+
+impl FromIterator<(MapKey, LoxValue)> for LoxMap {
+    fn from_iter<I: IntoIterator<Item = (MapKey, LoxValue)>>(entries: I) -> Self {
+        let mut map = LoxMap::new();
+        for (key, value) in entries {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+/// The method `name` of `map`, bound to it, or `None` if maps have no such
+/// method. The lists `keys` and `values` build are charged to `allocations`.
+pub(crate) fn method(
+    map: &Rc<RefCell<LoxMap>>,
+    name: &str,
+    allocations: &Rc<Allocations>,
+) -> Option<LoxValue> {
+    let arity = match name {
+        "keys" | "values" | "len" => 0,
+        "has" | "remove" => 1,
+        _ => return None,
+    };
+
+    let map = Rc::clone(map);
+    let method = name.to_string();
+    let allocations = Rc::clone(allocations);
+    let native = NativeFunction::new(name, arity, move |args| {
+        call(&map, &method, args, &allocations)
+    });
+    Some(LoxValue::NativeFunction(Rc::new(native)))
+}
+
+fn call(
+    map: &RefCell<LoxMap>,
+    method: &str,
+    args: &[LoxValue],
+    allocations: &Allocations,
+) -> NativeResult {
+    let value = match method {
+        "keys" => allocations.charged(LoxValue::list(
+            map.borrow()
+                .iter()
+                .map(|(key, _)| LoxValue::from(key.clone()))
+                .collect(),
+        ))?,
+        "values" => allocations.charged(LoxValue::list(
+            map.borrow()
+                .iter()
+                .map(|(_, value)| value.clone())
+                .collect(),
+        ))?,
+        "len" => LoxValue::from(map.borrow().len() as f64),
+        "has" => {
+            let key = MapKey::try_from(args[0].clone())?;
+            LoxValue::from(map.borrow().contains_key(&key))
+        }
+        "remove" => {
+            let key = MapKey::try_from(args[0].clone())?;
+            let removed = map.borrow_mut().remove(&key);
+            removed.ok_or_else(|| undefined_key(&key))?
+        }
+        _ => unreachable!("no map method '{}'", method),
+    };
+
+    Ok(value)
+}
+
+pub(crate) fn undefined_key(key: &MapKey) -> String {
+    format!("Undefined key '{}'.", key.stringify())
+}
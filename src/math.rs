@@ -0,0 +1,92 @@
+//! The `math` namespace: `math.floor(x)`, `math.pi` and so on.
+//!
+//! - Rounding: `floor`, `ceil`, `round` (halves round away from zero), `abs`.
+//! - Powers: `sqrt`, `pow(x, y)`, `exp`, and the logarithms `log` (natural),
+//!   `log2` and `log10`.
+//! - Trigonometry, in radians: `sin`, `cos`, `tan`, `asin`, `acos`, `atan`
+//!   and `atan2(y, x)`.
+//! - `min(a, b)`, `max(a, b)` and `isNaN(x)`.
+//! - Constants: `pi`, `e`, `inf` and `nan`.
+//!
+//! Every function takes numbers only. Results follow IEEE 754, so
+//! `math.sqrt(-1)` is `nan` rather than an error.
+
+use std::{f64::consts, rc::Rc};
+
+use crate::{
+    host::Namespace,
+    interpreter::LoxValue,
+    native::{NativeFunction, NativeResult},
+    scanner::token::LiteralType,
+};
+
+pub(crate) fn namespace() -> Namespace {
+    let mut members = vec![
+        unary("floor", f64::floor),
+        unary("ceil", f64::ceil),
+        unary("round", f64::round),
+        unary("abs", f64::abs),
+        unary("sqrt", f64::sqrt),
+        unary("exp", f64::exp),
+        unary("log", f64::ln),
+        unary("log2", f64::log2),
+        unary("log10", f64::log10),
+        unary("sin", f64::sin),
+        unary("cos", f64::cos),
+        unary("tan", f64::tan),
+        unary("asin", f64::asin),
+        unary("acos", f64::acos),
+        unary("atan", f64::atan),
+        binary("atan2", f64::atan2),
+        binary("pow", f64::powf),
+        binary("min", f64::min),
+        binary("max", f64::max),
+        function("isNaN", 1, |args| {
+            Ok(LoxValue::from(number("isNaN", &args[0])?.is_nan()))
+        }),
+    ];
+
+    for (name, value) in [
+        ("pi", consts::PI),
+        ("e", consts::E),
+        ("inf", f64::INFINITY),
+        ("nan", f64::NAN),
+    ] {
+        members.push((name.to_string(), LoxValue::from(value)));
+    }
+
+    Namespace::new("math", members)
+}
+
+fn unary(name: &'static str, operation: fn(f64) -> f64) -> (String, LoxValue) {
+    function(name, 1, move |args| {
+        Ok(LoxValue::from(operation(number(name, &args[0])?)))
+    })
+}
+
+fn binary(name: &'static str, operation: fn(f64, f64) -> f64) -> (String, LoxValue) {
+    function(name, 2, move |args| {
+        let left = number(name, &args[0])?;
+        let right = number(name, &args[1])?;
+        Ok(LoxValue::from(operation(left, right)))
+    })
+}
+
+fn function<F>(name: &str, arity: usize, function: F) -> (String, LoxValue)
+where
+    F: Fn(&[LoxValue]) -> NativeResult + 'static,
+{
+    let native = NativeFunction::new(name, arity, function);
+    (name.to_string(), LoxValue::NativeFunction(Rc::new(native)))
+}
+
+fn number(function: &str, value: &LoxValue) -> Result<f64, String> {
+    match value {
+        LoxValue::Literal(LiteralType::LNumber(number)) => Ok(*number),
+        other => Err(format!(
+            "math.{}() expects numbers but got {}.",
+            function,
+            other.type_name()
+        )),
+    }
+}
@@ -0,0 +1,81 @@
+use std::fmt;
+
+use crate::{errors::Error, interpreter::LoxValue};
+
+/// The signature of a Rust function callable from Lox.
+pub type NativeFn = dyn Fn(&[LoxValue]) -> NativeResult;
+
+pub type NativeResult = Result<LoxValue, NativeError>;
+
+/// Why a native function or host method failed.
+///
+/// Most natives fail with a message, usually through `?` or `.into()` on a
+/// `String`. Natives that run Lox themselves, e.g. by calling `Lox::eval`,
+/// pass the `Error` they got on instead, so it keeps its line and trace.
+#[derive(Debug)]
+pub enum NativeError {
+    /// Becomes a runtime error reported at the call site.
+    Message(String),
+    /// Passed on as is, with the failed call added to its trace.
+    Error(Error),
+}
+
+impl From<String> for NativeError {
+    fn from(message: String) -> Self {
+        NativeError::Message(message)
+    }
+}
+
+impl From<&str> for NativeError {
+    fn from(message: &str) -> Self {
+        NativeError::Message(message.to_string())
+    }
+}
+
+impl From<Error> for NativeError {
+    fn from(error: Error) -> Self {
+        NativeError::Error(error)
+    }
+}
+
+/// A function implemented by the host and exposed to scripts as a value.
+pub struct NativeFunction {
+    pub name: String,
+    pub arity: usize,
+    function: Box<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new<F>(name: &str, arity: usize, function: F) -> Self
+    where
+        F: Fn(&[LoxValue]) -> NativeResult + 'static,
+    {
+        NativeFunction {
+            name: name.to_string(),
+            arity,
+            function: Box::new(function),
+        }
+    }
+
+    pub fn check_arity(&self, count: usize) -> Result<(), String> {
+        if count == self.arity {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected {} arguments but got {}.",
+                self.arity, count
+            ))
+        }
+    }
+
+    /// Calls the function. The caller has already checked the arity.
+    pub fn call(&self, arguments: &[LoxValue]) -> NativeResult {
+        (self.function)(arguments)
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<native fn {}>", self.name)
+    }
+}
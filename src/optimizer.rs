@@ -0,0 +1,79 @@
+use crate::{
+    ast::{walk_expr_mut, Expr, Stmt, VisitorMut},
+    errors::{Error, LoxResult},
+    scanner::token::LiteralType,
+};
+
+/// Folds expressions whose operands are all literals into a single literal,
+/// so `2 + 7` is computed once here rather than every time it runs. Operations
+/// that would fail at runtime, like `"a" - 1`, are reported up front instead.
+pub struct Optimizer {
+    error: Option<Error>,
+}
+
+impl Optimizer {
+    pub fn new() -> Self {
+        Optimizer { error: None }
+    }
+
+    pub fn optimize(&mut self, statements: &mut [Stmt]) -> LoxResult<()> {
+        for statement in statements.iter_mut() {
+            self.handle_stmt_mut(statement);
+            if let Some(error) = self.error.take() {
+                return Err(error);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fold(&mut self, expr: &Expr) -> Option<LiteralType> {
+        let result = match expr {
+            Expr::Grouping(inner) => match inner.as_ref() {
+                Expr::Literal(literal) => Ok(literal.clone()),
+                _ => return None,
+            },
+            Expr::Unary(operator, right) => match right.as_ref() {
+                Expr::Literal(literal) => literal.clone().apply_unary(operator.token_type),
+                _ => return None,
+            },
+            Expr::Binary(left, operator, right) => match (left.as_ref(), right.as_ref()) {
+                (Expr::Literal(left), Expr::Literal(right)) => {
+                    left.clone().apply(operator.token_type, right.clone())
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        match result {
+            Ok(literal) => Some(literal),
+            Err(message) => {
+                let line = match expr {
+                    Expr::Unary(operator, _) | Expr::Binary(_, operator, _) => operator.line,
+                    _ => 0,
+                };
+                self.error
+                    .get_or_insert(Error::OptimizerError(line, message));
+                None
+            }
+        }
+    }
+}
+
+impl Default for Optimizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VisitorMut for Optimizer {
+    fn handle_expr_mut(&mut self, expr: &mut Expr) {
+        // Fold the children first so constants bubble up through the tree.
+        walk_expr_mut(self, expr);
+
+        if let Some(literal) = self.fold(expr) {
+            *expr = Expr::Literal(literal);
+        }
+    }
+}
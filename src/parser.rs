@@ -7,23 +7,141 @@ use crate::{
     },
 };
 
-pub struct Parser {
-    tokens: Vec<Token>,
-    current: usize,
+/// How tightly an infix operator binds, from loosest to tightest.
+#[derive(Copy, Clone, PartialEq, PartialOrd)]
+enum Precedence {
+    None,
+    Assignment,
+    Equality,
+    Comparison,
+    Term,
+    Factor,
+    Unary,
+    Call,
+    Primary,
 }
 
-impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, current: 0 }
+impl Precedence {
+    fn next(self) -> Precedence {
+        match self {
+            Precedence::None => Precedence::Assignment,
+            Precedence::Assignment => Precedence::Equality,
+            Precedence::Equality => Precedence::Comparison,
+            Precedence::Comparison => Precedence::Term,
+            Precedence::Term => Precedence::Factor,
+            Precedence::Factor => Precedence::Unary,
+            Precedence::Unary => Precedence::Call,
+            Precedence::Call | Precedence::Primary => Precedence::Primary,
+        }
     }
+}
 
-    pub fn parse(&mut self) -> LoxResult<Vec<Stmt>> {
-        let mut statements = vec![];
-        while !self.is_at_end() {
-            statements.push(self.declaration()?);
+/// How deeply statements and expressions may nest unless the caller says
+/// otherwise. Every pass over the tree recurses once per level, so this is
+/// kept well below what the Rust stack can take.
+pub const DEFAULT_MAX_NESTING_DEPTH: usize = 256;
+
+type PrefixParselet<I> = fn(&mut Parser<I>) -> LoxResult<Expr>;
+type InfixParselet<I> = fn(&mut Parser<I>, Expr) -> LoxResult<Expr>;
+
+/// How a token is parsed at the start of an expression (`prefix`) and after a
+/// complete left operand (`infix`), along with the precedence of the infix
+/// form.
+struct ParseRule<I: Iterator<Item = LoxResult<Token>>> {
+    prefix: Option<PrefixParselet<I>>,
+    infix: Option<InfixParselet<I>>,
+    precedence: Precedence,
+}
+
+impl<I: Iterator<Item = LoxResult<Token>>> ParseRule<I> {
+    fn new(
+        prefix: Option<PrefixParselet<I>>,
+        infix: Option<InfixParselet<I>>,
+        precedence: Precedence,
+    ) -> Self {
+        ParseRule {
+            prefix,
+            infix,
+            precedence,
         }
+    }
+}
 
-        Ok(statements)
+/// Parses statements out of a stream of tokens, pulling each token only when
+/// it is needed. The first scanner error stops the stream and is reported in
+/// place of whatever the parser made of the truncated input.
+///
+/// Nesting deeper than the maximum depth is a parse error. A chain of infix
+/// operators counts one level per operator, since the tree it builds is that
+/// deep even though the parser doesn't recurse for it.
+pub struct Parser<I: Iterator<Item = LoxResult<Token>>> {
+    tokens: I,
+    current: Token,
+    previous: Option<Token>,
+    error: Option<Error>,
+    depth: usize,
+    max_depth: Option<usize>,
+}
+
+impl<I: Iterator<Item = LoxResult<Token>>> Parser<I> {
+    pub fn new(tokens: I) -> Self {
+        let mut parser = Parser {
+            tokens,
+            current: Token::new(TokenType::EOF, "".to_string(), None, 1),
+            previous: None,
+            error: None,
+            depth: 0,
+            max_depth: Some(DEFAULT_MAX_NESTING_DEPTH),
+        };
+        parser.current = parser.next_token();
+        parser
+    }
+
+    /// Limits how deeply statements and expressions may nest, or lifts the
+    /// limit with `None`. Defaults to `DEFAULT_MAX_NESTING_DEPTH`.
+    pub fn with_max_depth(mut self, depth: Option<usize>) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// The parse rule for `token_type`, or `None` if it can't start or
+    /// continue an expression. A new operator only needs an entry here, plus
+    /// a parselet if none of the existing ones fit.
+    #[rustfmt::skip]
+    fn rule(token_type: TokenType) -> Option<ParseRule<I>> {
+        use Precedence as P;
+
+        let rule = match token_type {
+            TokenType::LeftParen => ParseRule::new(Some(Self::grouping), Some(Self::call), P::Call),
+            TokenType::LeftBrace => ParseRule::new(Some(Self::map), None, P::None),
+            TokenType::LeftBracket => ParseRule::new(Some(Self::list), Some(Self::index), P::Call),
+            TokenType::Dot => ParseRule::new(None, Some(Self::dot), P::Call),
+            TokenType::Minus => ParseRule::new(Some(Self::unary), Some(Self::binary), P::Term),
+            TokenType::Plus => ParseRule::new(None, Some(Self::binary), P::Term),
+            TokenType::Slash => ParseRule::new(None, Some(Self::binary), P::Factor),
+            TokenType::Star => ParseRule::new(None, Some(Self::binary), P::Factor),
+            TokenType::Bang => ParseRule::new(Some(Self::unary), None, P::None),
+            TokenType::BangEqual => ParseRule::new(None, Some(Self::binary), P::Equality),
+            TokenType::Equal => ParseRule::new(None, Some(Self::assignment), P::Assignment),
+            TokenType::EqualEqual => ParseRule::new(None, Some(Self::binary), P::Equality),
+            TokenType::Greater => ParseRule::new(None, Some(Self::binary), P::Comparison),
+            TokenType::GreaterEqual => ParseRule::new(None, Some(Self::binary), P::Comparison),
+            TokenType::Less => ParseRule::new(None, Some(Self::binary), P::Comparison),
+            TokenType::LessEqual => ParseRule::new(None, Some(Self::binary), P::Comparison),
+            TokenType::Identifier => ParseRule::new(Some(Self::variable), None, P::None),
+            TokenType::StringLiteral => ParseRule::new(Some(Self::literal), None, P::None),
+            TokenType::Number => ParseRule::new(Some(Self::literal), None, P::None),
+            TokenType::False => ParseRule::new(Some(Self::literal), None, P::None),
+            TokenType::Nil => ParseRule::new(Some(Self::literal), None, P::None),
+            TokenType::True => ParseRule::new(Some(Self::literal), None, P::None),
+            _ => return None,
+        };
+
+        Some(rule)
+    }
+
+    pub fn parse(&mut self) -> LoxResult<Vec<Stmt>> {
+        self.collect()
     }
 
     fn declaration(&mut self) -> LoxResult<Stmt> {
@@ -45,134 +163,234 @@ impl Parser {
         self.consume(
             TokenType::Semicolon,
             "Expect ';' after variable declaration.",
-        );
+        )?;
         Ok(Stmt::Var(name.clone(), initializer))
     }
 
     fn statement(&mut self) -> LoxResult<Stmt> {
+        let depth = self.depth;
+        let statement = self.nest().and_then(|_| self.statement_within());
+        self.depth = depth;
+        statement
+    }
+
+    fn statement_within(&mut self) -> LoxResult<Stmt> {
+        if self.match_types(vec![TokenType::For]) {
+            return self.for_in_statement();
+        }
         if self.match_types(vec![TokenType::Print]) {
             return self.print_statement();
         }
-        return self.expression_statement();
+        if self.match_types(vec![TokenType::LeftBrace]) {
+            return Ok(Stmt::Block(self.block()?));
+        }
+        self.expression_statement()
+    }
+
+    fn for_in_statement(&mut self) -> LoxResult<Stmt> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
+        let name = self.consume(TokenType::Identifier, "Expect loop variable name.")?;
+        self.consume(TokenType::In, "Expect 'in' after loop variable.")?;
+        let iterable = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after for clauses.")?;
+        let body = self.statement()?;
+        Ok(Stmt::ForIn(name, Box::new(iterable), Box::new(body)))
+    }
+
+    fn block(&mut self) -> LoxResult<Vec<Stmt>> {
+        let mut statements = vec![];
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            statements.push(self.declaration()?);
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after block.")?;
+        Ok(statements)
     }
 
     fn print_statement(&mut self) -> LoxResult<Stmt> {
         let value = self.expression()?;
-        self.consume(TokenType::Semicolon, "Expect ; after value.");
+        self.consume(TokenType::Semicolon, "Expect ; after value.")?;
         Ok(Stmt::Print(Box::new(value)))
     }
 
     fn expression_statement(&mut self) -> LoxResult<Stmt> {
         let value = self.expression()?;
-        self.consume(TokenType::Semicolon, "Expect ; after expression.");
+        self.consume(TokenType::Semicolon, "Expect ; after expression.")?;
         Ok(Stmt::Expression(Box::new(value)))
     }
 
     fn expression(&mut self) -> LoxResult<Expr> {
-        self.assignment()
+        self.parse_precedence(Precedence::Assignment)
     }
 
-    fn assignment(&mut self) -> LoxResult<Expr> {
-        let expr = self.equality()?;
-
-        if self.match_types(vec![TokenType::Equal]) {
-            let value = self.assignment()?;
+    /// Parses an expression whose operators all bind at least as tightly as
+    /// `precedence`, dispatching on the rule table.
+    fn parse_precedence(&mut self, precedence: Precedence) -> LoxResult<Expr> {
+        let depth = self.depth;
+        let expr = self.parse_precedence_within(precedence);
+        self.depth = depth;
+        expr
+    }
 
-            if let Expr::Variable(token) = expr {
-                return Ok(Expr::Assign(token, Box::new(value)));
+    fn parse_precedence_within(&mut self, precedence: Precedence) -> LoxResult<Expr> {
+        self.nest()?;
+        let prefix = match Self::rule(self.peek().token_type).and_then(|rule| rule.prefix) {
+            Some(prefix) => prefix,
+            None => {
+                return Err(Error::ParseError(
+                    self.peek().line,
+                    "Expect expression".to_string(),
+                ))
             }
+        };
+        self.advance();
+        let mut expr = prefix(self)?;
 
-            return Err(Error::ParseError(
-                self.peek().line,
-                "Invalid assignment target".to_string(),
-            ));
+        while precedence <= self.infix_precedence(self.peek().token_type) {
+            let infix = Self::rule(self.peek().token_type).unwrap().infix.unwrap();
+            self.nest()?;
+            self.advance();
+            expr = infix(self, expr)?;
         }
 
         Ok(expr)
     }
 
-    fn equality(&mut self) -> LoxResult<Expr> {
-        let mut expr = self.comparison()?;
+    /// Goes one level deeper, failing if that is past the maximum depth. The
+    /// caller restores the depth once it is done.
+    fn nest(&mut self) -> LoxResult<()> {
+        self.depth += 1;
+        if self.max_depth.is_some_and(|max| self.depth > max) {
+            return Err(Error::ParseError(
+                self.peek().line,
+                "Nesting is too deep.".to_string(),
+            ));
+        }
+        Ok(())
+    }
 
-        while self.match_types(vec![TokenType::BangEqual, TokenType::EqualEqual]) {
-            let operator = self.previous().clone();
-            let right = self.comparison()?;
-            expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
+    fn infix_precedence(&self, token_type: TokenType) -> Precedence {
+        match Self::rule(token_type) {
+            Some(rule) if rule.infix.is_some() => rule.precedence,
+            _ => Precedence::None,
         }
-        Ok(expr)
     }
 
-    fn comparison(&mut self) -> LoxResult<Expr> {
-        let mut expr = self.term()?;
+    fn assignment(&mut self, target: Expr) -> LoxResult<Expr> {
+        let line = self.previous().line;
+        // Parsing the right side at the same level makes `=` right-associative.
+        let value = self.parse_precedence(Precedence::Assignment)?;
 
-        while self.match_types(vec![
-            TokenType::Greater,
-            TokenType::GreaterEqual,
-            TokenType::Less,
-            TokenType::LessEqual,
-        ]) {
-            let operator = self.previous().clone();
-            let right = self.term()?;
-            expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
+        match target {
+            Expr::Variable(token) => return Ok(Expr::Assign(token, Box::new(value))),
+            Expr::Get(object, name) => return Ok(Expr::Set(object, name, Box::new(value))),
+            Expr::Index(object, bracket, index) => {
+                return Ok(Expr::SetIndex(object, bracket, index, Box::new(value)))
+            }
+            _ => (),
         }
 
-        Ok(expr)
+        Err(Error::ParseError(
+            line,
+            "Invalid assignment target".to_string(),
+        ))
     }
 
-    fn term(&mut self) -> LoxResult<Expr> {
-        let mut expr = self.factor()?;
+    fn binary(&mut self, left: Expr) -> LoxResult<Expr> {
+        let operator = self.previous().clone();
+        let precedence = self.infix_precedence(operator.token_type);
+        let right = self.parse_precedence(precedence.next())?;
+        Ok(Expr::Binary(Box::new(left), operator, Box::new(right)))
+    }
 
-        while self.match_types(vec![TokenType::Minus, TokenType::Plus]) {
-            let operator = self.previous().clone();
-            let right = self.factor()?;
-            expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
+    fn call(&mut self, callee: Expr) -> LoxResult<Expr> {
+        let mut arguments = vec![];
+        if !self.check(TokenType::RightParen) {
+            loop {
+                if arguments.len() >= 255 {
+                    return Err(Error::ParseError(
+                        self.peek().line,
+                        "Can't have more than 255 arguments.".to_string(),
+                    ));
+                }
+                arguments.push(self.expression()?);
+                if !self.match_types(vec![TokenType::Comma]) {
+                    break;
+                }
+            }
         }
 
-        Ok(expr)
+        let paren = self.consume(TokenType::RightParen, "Expect ')' after arguments.")?;
+        Ok(Expr::Call(Box::new(callee), paren, arguments))
     }
 
-    fn factor(&mut self) -> LoxResult<Expr> {
-        let mut expr = self.unary()?;
-        while self.match_types(vec![TokenType::Slash, TokenType::Star]) {
-            let operator = self.previous().clone();
-            let right = self.unary()?;
-            expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
-        }
-        Ok(expr)
+    fn index(&mut self, object: Expr) -> LoxResult<Expr> {
+        let index = self.expression()?;
+        let bracket = self.consume(TokenType::RightBracket, "Expect ']' after index.")?;
+        Ok(Expr::Index(Box::new(object), bracket, Box::new(index)))
+    }
+
+    fn dot(&mut self, object: Expr) -> LoxResult<Expr> {
+        let name = self.consume(TokenType::Identifier, "Expect property name after '.'.")?;
+        Ok(Expr::Get(Box::new(object), name))
     }
 
     fn unary(&mut self) -> LoxResult<Expr> {
-        if self.match_types(vec![TokenType::Bang, TokenType::Minus]) {
-            let operator = self.previous().clone();
-            let right = self.unary()?;
-            return Ok(Expr::Unary(operator, Box::new(right)));
-        }
-        self.primary()
+        let operator = self.previous().clone();
+        let right = self.parse_precedence(Precedence::Unary)?;
+        Ok(Expr::Unary(operator, Box::new(right)))
     }
 
-    fn primary(&mut self) -> LoxResult<Expr> {
-        if self.match_types(vec![TokenType::False]) {
-            return Ok(Expr::Literal(LiteralType::LBoolean(false)));
-        }
-        if self.match_types(vec![TokenType::True]) {
-            return Ok(Expr::Literal(LiteralType::LBoolean(true)));
-        }
-        if self.match_types(vec![TokenType::Nil]) {
-            return Ok(Expr::Literal(LiteralType::LNil));
-        }
-        if self.match_types(vec![TokenType::Number, TokenType::StringLiteral]) {
-            return Ok(Expr::Literal(self.previous().clone().literal.unwrap()));
-        }
-        if self.match_types(vec![TokenType::Identifier]) {
-            return Ok(Expr::Variable(self.previous().clone()));
+    fn list(&mut self) -> LoxResult<Expr> {
+        let mut items = vec![];
+        if !self.check(TokenType::RightBracket) {
+            loop {
+                items.push(self.expression()?);
+                if !self.match_types(vec![TokenType::Comma]) {
+                    break;
+                }
+            }
         }
-        if self.match_types(vec![TokenType::LeftParen]) {
-            let expr = self.expression()?;
-            self.consume(TokenType::RightParen, "Expect ')' after expression")?;
-            return Ok(Expr::Grouping(Box::new(expr)));
+
+        self.consume(TokenType::RightBracket, "Expect ']' after list items.")?;
+        Ok(Expr::List(items))
+    }
+
+    fn map(&mut self) -> LoxResult<Expr> {
+        let mut entries = vec![];
+        if !self.check(TokenType::RightBrace) {
+            loop {
+                let key = self.expression()?;
+                self.consume(TokenType::Colon, "Expect ':' after map key.")?;
+                entries.push((key, self.expression()?));
+                if !self.match_types(vec![TokenType::Comma]) {
+                    break;
+                }
+            }
         }
 
-        Err(Error::ParseError(self.peek().line, "Expect expression".to_string()))
+        let brace = self.consume(TokenType::RightBrace, "Expect '}' after map entries.")?;
+        Ok(Expr::Map(brace, entries))
+    }
+
+    fn grouping(&mut self) -> LoxResult<Expr> {
+        let expr = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after expression")?;
+        Ok(Expr::Grouping(Box::new(expr)))
+    }
+
+    fn literal(&mut self) -> LoxResult<Expr> {
+        let literal = match self.previous().token_type {
+            TokenType::False => LiteralType::LBoolean(false),
+            TokenType::True => LiteralType::LBoolean(true),
+            TokenType::Nil => LiteralType::LNil,
+            _ => self.previous().literal.clone().unwrap(),
+        };
+        Ok(Expr::Literal(literal))
+    }
+
+    fn variable(&mut self) -> LoxResult<Expr> {
+        Ok(Expr::Variable(self.previous().clone()))
     }
 
     fn consume(&mut self, token_type: TokenType, message: &str) -> LoxResult<Token> {
@@ -206,18 +424,30 @@ impl Parser {
     }
 
     fn peek(&self) -> &Token {
-        return self.tokens.get(self.current).unwrap();
+        &self.current
     }
 
     fn advance(&mut self) -> &Token {
         if !self.is_at_end() {
-            self.current += 1;
+            let next = self.next_token();
+            self.previous = Some(std::mem::replace(&mut self.current, next));
         }
         self.previous()
     }
 
     fn previous(&self) -> &Token {
-        self.tokens.get(self.current - 1).unwrap()
+        self.previous.as_ref().unwrap()
+    }
+
+    fn next_token(&mut self) -> Token {
+        match self.tokens.next() {
+            Some(Ok(token)) => token,
+            Some(Err(error)) => {
+                self.error.get_or_insert(error);
+                Token::new(TokenType::EOF, "".to_string(), None, self.current.line)
+            }
+            None => Token::new(TokenType::EOF, "".to_string(), None, self.current.line),
+        }
     }
 
     fn synchronize(&mut self) {
@@ -244,3 +474,22 @@ impl Parser {
         }
     }
 }
+
+impl<I: Iterator<Item = LoxResult<Token>>> Iterator for Parser<I> {
+    type Item = LoxResult<Stmt>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_at_end() {
+            return self.error.take().map(Err);
+        }
+
+        let statement = self.declaration();
+        if statement.is_err() {
+            self.synchronize();
+        }
+        Some(match self.error.take() {
+            Some(error) => Err(error),
+            None => statement,
+        })
+    }
+}
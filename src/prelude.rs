@@ -0,0 +1,118 @@
+//! The core built-ins every script can use:
+//!
+//! - `type(x)` is the name of `x`'s type: `"number"`, `"string"`,
+//!   `"boolean"`, `"nil"`, `"function"`, `"list"`, `"map"` or `"object"`.
+//! - `str(x)` is `x` as `print` would show it.
+//! - `num(x)` is `x` if it's a number, or the number a string spells out,
+//!   ignoring surrounding whitespace. Anything else is an error.
+//! - `len(x)` is the number of characters in a string, items in a list or
+//!   entries in a map.
+//! - `input()` reads a line from the interpreter's input, without its line
+//!   ending, or returns `nil` at end of input.
+//! - `assert(cond, message)` fails with `message` unless `cond` is truthy.
+//! - `fromCharCode(code)` is the one-character string for a Unicode code
+//!   point, the inverse of the string method `charCodeAt`.
+//! - `range(start, end)` counts from `start` up to but not including `end`,
+//!   for `for (i in range(0, 10))` loops.
+//! - `exit(code)` stops the script with an integer exit code. It needs the
+//!   `exit` capability, and the host sees it as `Error::Exit`.
+//!
+//! `clock()` and the other natives that reach outside the interpreter live in
+//! `system` and depend on the script's capabilities.
+
+use std::{cell::RefCell, io::BufRead, rc::Rc};
+
+use crate::{
+    allocations::Allocations,
+    capabilities::Capabilities,
+    errors::Error,
+    interpreter::{self, Interpreter, LoxValue},
+    range,
+    scanner::token::LiteralType,
+    strings,
+};
+
+pub(crate) fn define(
+    interpreter: &Interpreter,
+    input: &Rc<RefCell<Box<dyn BufRead>>>,
+    capabilities: &Rc<RefCell<Capabilities>>,
+    allocations: &Rc<Allocations>,
+) {
+    let charge = allocations.clone();
+    interpreter.define_native("type", 1, move |args| {
+        Ok(charge.charged(LoxValue::from(args[0].type_name()))?)
+    });
+
+    let charge = allocations.clone();
+    interpreter.define_native("str", 1, move |args| {
+        Ok(charge.charged(LoxValue::from(args[0].stringify()))?)
+    });
+
+    interpreter.define_native("num", 1, |args| match &args[0] {
+        LoxValue::Literal(LiteralType::LNumber(number)) => Ok(LoxValue::from(*number)),
+        LoxValue::Literal(LiteralType::LString(string)) => string
+            .trim()
+            .parse::<f64>()
+            .map(LoxValue::from)
+            .map_err(|_| format!("Could not convert '{}' to a number.", string).into()),
+        other => Err(format!(
+            "num() expects a number or string but got {}.",
+            other.type_name()
+        )
+        .into()),
+    });
+
+    interpreter.define_native("len", 1, |args| match &args[0] {
+        LoxValue::Literal(LiteralType::LString(string)) => {
+            Ok(LoxValue::from(string.chars().count() as f64))
+        }
+        LoxValue::List(list) => Ok(LoxValue::from(list.borrow().len() as f64)),
+        LoxValue::Map(map) => Ok(LoxValue::from(map.borrow().len() as f64)),
+        other => Err(format!(
+            "len() expects a string, list or map but got {}.",
+            other.type_name()
+        )
+        .into()),
+    });
+
+    let charge = allocations.clone();
+    interpreter.define_native("fromCharCode", 1, move |args| {
+        Ok(charge.charged(strings::from_char_code(args)?)?)
+    });
+
+    interpreter.define_native("range", 2, range::range);
+
+    let input = input.clone();
+    let charge = allocations.clone();
+    interpreter.define_native("input", 0, move |_| {
+        let line = LoxValue::from(interpreter::read_line(&input)?);
+        Ok(charge.charged(line)?)
+    });
+
+    interpreter.define_native("assert", 2, |args| {
+        if args[0].is_truthy() {
+            Ok(LoxValue::nil())
+        } else {
+            Err(format!("Assertion failed: {}", args[1].stringify()).into())
+        }
+    });
+
+    let allowed = capabilities.clone();
+    interpreter.define_native("exit", 1, move |args| {
+        allowed.borrow().check_exit()?;
+        let code = match &args[0] {
+            LoxValue::Literal(LiteralType::LNumber(number))
+                if number.fract() == 0.0
+                    && (i32::MIN as f64..=i32::MAX as f64).contains(number) =>
+            {
+                *number as i32
+            }
+            other => {
+                return Err(
+                    format!("exit() expects an integer but got {}.", other.stringify()).into(),
+                )
+            }
+        };
+        Err(Error::Exit(code).into())
+    });
+}
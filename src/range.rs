@@ -0,0 +1,107 @@
+//! `range(start, end)`: the numbers from `start` up to but not including
+//! `end`, counting by one. Ranges are lazy, so `for (i in range(0, 1000000))`
+//! never builds a list; loops walk them through the same `iter()`/`next()`
+//! methods as any other iterable host object. The bounds must be within
+//! 2^53 of zero, so that counting up by one never gets stuck.
+
+use std::cell::Cell;
+
+use crate::{
+    host::HostObject, interpreter::LoxValue, native::NativeResult, scanner::token::LiteralType,
+};
+
+pub(crate) fn range(args: &[LoxValue]) -> NativeResult {
+    match (&args[0], &args[1]) {
+        (
+            LoxValue::Literal(LiteralType::LNumber(start)),
+            LoxValue::Literal(LiteralType::LNumber(end)),
+        ) => Ok(LoxValue::host_object(Range {
+            start: bound(*start)?,
+            end: bound(*end)?,
+        })),
+        (LoxValue::Literal(LiteralType::LNumber(_)), other) | (other, _) => {
+            Err(format!("range() expects numbers but got {}.", other.type_name()).into())
+        }
+    }
+}
+
+/// Past 2^53, adding one to a number can leave it unchanged.
+const MAX_BOUND: f64 = 9007199254740992.0;
+
+fn bound(number: f64) -> Result<f64, String> {
+    if number.abs() <= MAX_BOUND {
+        Ok(number)
+    } else {
+        Err(format!(
+            "range() expects bounds between -2^53 and 2^53 but got {}.",
+            LoxValue::from(number).stringify()
+        ))
+    }
+}
+
+struct Range {
+    start: f64,
+    end: f64,
+}
+
+impl HostObject for Range {
+    fn type_name(&self) -> &str {
+        "Range"
+    }
+
+    fn get(&self, name: &str) -> Option<LoxValue> {
+        match name {
+            "start" => Some(LoxValue::from(self.start)),
+            "end" => Some(LoxValue::from(self.end)),
+            _ => None,
+        }
+    }
+
+    fn call_method(&self, name: &str, arguments: &[LoxValue]) -> NativeResult {
+        match name {
+            "iter" => {
+                no_arguments(arguments)?;
+                Ok(LoxValue::host_object(RangeIterator {
+                    next: Cell::new(self.start),
+                    end: self.end,
+                }))
+            }
+            _ => Err(format!("Undefined method '{}'.", name).into()),
+        }
+    }
+}
+
+struct RangeIterator {
+    next: Cell<f64>,
+    end: f64,
+}
+
+impl HostObject for RangeIterator {
+    fn type_name(&self) -> &str {
+        "RangeIterator"
+    }
+
+    fn call_method(&self, name: &str, arguments: &[LoxValue]) -> NativeResult {
+        match name {
+            "next" => {
+                no_arguments(arguments)?;
+                let next = self.next.get();
+                if next < self.end {
+                    self.next.set(next + 1.0);
+                    Ok(LoxValue::from(next))
+                } else {
+                    Ok(LoxValue::nil())
+                }
+            }
+            _ => Err(format!("Undefined method '{}'.", name).into()),
+        }
+    }
+}
+
+fn no_arguments(arguments: &[LoxValue]) -> Result<(), String> {
+    if arguments.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Expected 0 arguments but got {}.", arguments.len()))
+    }
+}
@@ -1,6 +1,5 @@
-#[path = "errors.rs"]
-mod errors;
 pub mod token;
+#[allow(non_snake_case)]
 pub mod tokenType;
 
 use std::collections::HashMap;
@@ -10,12 +9,17 @@ use self::tokenType::TokenType;
 use crate::errors::{Error, LoxResult};
 
 pub struct Scanner<'a> {
-    source: String,
+    source: Vec<char>,
     pub tokens: Vec<Token>,
     start: usize,
     current: usize,
     line: usize,
+    line_start: usize,
+    /// Where the token being scanned starts; a string can run over lines.
+    start_line: usize,
+    start_column: usize,
     keywords: HashMap<&'a str, TokenType>,
+    finished: bool,
 }
 
 impl<'a> Scanner<'a> {
@@ -28,6 +32,7 @@ impl<'a> Scanner<'a> {
         keywords.insert("for", TokenType::For);
         keywords.insert("fun", TokenType::Fun);
         keywords.insert("if", TokenType::If);
+        keywords.insert("in", TokenType::In);
         keywords.insert("nil", TokenType::Nil);
         keywords.insert("or", TokenType::Or);
         keywords.insert("print", TokenType::Print);
@@ -39,24 +44,26 @@ impl<'a> Scanner<'a> {
         keywords.insert("while", TokenType::While);
 
         Scanner {
-            source,
+            source: source.chars().collect(),
             tokens: Vec::new(),
             start: 0,
             current: 0,
             line: 1,
+            line_start: 0,
+            start_line: 1,
+            start_column: 1,
             keywords,
+            finished: false,
         }
     }
 
     pub fn scan_tokens(&mut self) -> LoxResult<()> {
-        while !self.is_at_end() {
-            self.start = self.current;
-            if let Err(error) = self.scan_token() {
-                error.report();
+        while let Some(result) = self.next() {
+            match result {
+                Ok(token) => self.tokens.push(token),
+                Err(error) => error.report(),
             }
         }
-        self.tokens
-            .push(Token::new(TokenType::EOF, "".to_string(), None, self.line));
 
         Ok(())
     }
@@ -65,69 +72,75 @@ impl<'a> Scanner<'a> {
         self.current >= self.source.len()
     }
 
-    fn scan_token(&mut self) -> LoxResult<()> {
+    fn scan_token(&mut self) -> LoxResult<Option<Token>> {
         let c = self.advance();
 
-        let mut result = Ok(());
-
-        match c {
-            '(' => self.add_token(TokenType::LeftParen, None),
-            ')' => self.add_token(TokenType::RightParen, None),
-            '{' => self.add_token(TokenType::LeftBrace, None),
-            '}' => self.add_token(TokenType::RightBrace, None),
-            ',' => self.add_token(TokenType::Comma, None),
-            '.' => self.add_token(TokenType::Dot, None),
-            '-' => self.add_token(TokenType::Minus, None),
-            '+' => self.add_token(TokenType::Plus, None),
-            ';' => self.add_token(TokenType::Semicolon, None),
-            '*' => self.add_token(TokenType::Star, None),
+        let token = match c {
+            '(' => Some(self.make_token(TokenType::LeftParen, None)),
+            ')' => Some(self.make_token(TokenType::RightParen, None)),
+            '{' => Some(self.make_token(TokenType::LeftBrace, None)),
+            '}' => Some(self.make_token(TokenType::RightBrace, None)),
+            '[' => Some(self.make_token(TokenType::LeftBracket, None)),
+            ']' => Some(self.make_token(TokenType::RightBracket, None)),
+            ':' => Some(self.make_token(TokenType::Colon, None)),
+            ',' => Some(self.make_token(TokenType::Comma, None)),
+            '.' => Some(self.make_token(TokenType::Dot, None)),
+            '-' => Some(self.make_token(TokenType::Minus, None)),
+            '+' => Some(self.make_token(TokenType::Plus, None)),
+            ';' => Some(self.make_token(TokenType::Semicolon, None)),
+            '*' => Some(self.make_token(TokenType::Star, None)),
             '!' => {
                 let mut token_type = TokenType::Bang;
                 if self.token_match('=') {
                     token_type = TokenType::BangEqual
                 };
-                self.add_token(token_type, None);
+                Some(self.make_token(token_type, None))
             }
             '=' => {
                 let mut token_type = TokenType::Equal;
                 if self.token_match('=') {
                     token_type = TokenType::EqualEqual
                 };
-                self.add_token(token_type, None);
+                Some(self.make_token(token_type, None))
             }
             '<' => {
                 let mut token_type = TokenType::Less;
                 if self.token_match('=') {
                     token_type = TokenType::LessEqual
                 };
-                self.add_token(token_type, None);
+                Some(self.make_token(token_type, None))
             }
             '>' => {
                 let mut token_type = TokenType::Greater;
                 if self.token_match('=') {
                     token_type = TokenType::GreaterEqual
                 };
-                self.add_token(token_type, None);
+                Some(self.make_token(token_type, None))
             }
             '/' => {
                 if self.token_match('/') {
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
+                    None
                 } else {
-                    self.add_token(TokenType::Slash, None);
+                    Some(self.make_token(TokenType::Slash, None))
                 }
             }
-            '"' => self.string()?,
-            ' ' | '\r' | '\t' => (),
-            '\n' => self.line += 1,
+            '"' => Some(self.string()?),
+            ' ' | '\r' | '\t' => None,
+            '\n' => {
+                self.line += 1;
+                self.line_start = self.current;
+                None
+            }
             _ => {
                 if self.is_digit(c) {
-                    self.number();
+                    Some(self.number())
                 } else if self.is_alpha(c) {
-                    self.identifier();
+                    Some(self.identifier())
                 } else {
-                    result = Err(Error::ScannerError(
+                    return Err(Error::ScannerError(
                         self.line,
                         "Unexpected character".to_string(),
                     ));
@@ -135,21 +148,21 @@ impl<'a> Scanner<'a> {
             }
         };
 
-        result
+        Ok(token)
     }
 
     fn peek(&self) -> char {
         if self.is_at_end() {
             return '\0';
         }
-        return self.source.chars().nth(self.current).unwrap();
+        self.source[self.current]
     }
 
     fn peek_next(&self) -> char {
         if self.current + 1 >= self.source.len() {
             return '\0';
         }
-        return self.source.chars().nth(self.current + 1).unwrap();
+        self.source[self.current + 1]
     }
 
     fn is_digit(&self, c: char) -> bool {
@@ -161,25 +174,29 @@ impl<'a> Scanner<'a> {
         characters.contains(c) || characters.to_uppercase().contains(c)
     }
 
+    fn is_alpha_numeric(&self, c: char) -> bool {
+        self.is_alpha(c) || self.is_digit(c)
+    }
+
     fn get_token_type_from_keyword(&self, text: &str) -> TokenType {
         match self.keywords.get(text) {
-            Some(t) => t.clone(),
+            Some(t) => *t,
             _ => TokenType::Identifier,
         }
     }
 
-    fn identifier(&mut self) {
-        while self.is_alpha(self.peek()) {
+    fn identifier(&mut self) -> Token {
+        while self.is_alpha_numeric(self.peek()) {
             self.advance();
         }
 
-        let text = self.source.get(self.start..self.current).unwrap();
+        let text = self.text(self.start, self.current);
 
-        let token_type = self.get_token_type_from_keyword(text);
-        self.add_token(token_type, None);
+        let token_type = self.get_token_type_from_keyword(&text);
+        self.make_token(token_type, None)
     }
 
-    fn number(&mut self) {
+    fn number(&mut self) -> Token {
         while self.is_digit(self.peek()) {
             self.advance();
         }
@@ -192,22 +209,18 @@ impl<'a> Scanner<'a> {
             }
         }
 
-        let value = self
-            .source
-            .get(self.start..self.current)
-            .unwrap()
-            .parse::<f64>()
-            .unwrap();
+        let value = self.text(self.start, self.current).parse::<f64>().unwrap();
         let literal = Some(LiteralType::LNumber(value));
-        self.add_token(TokenType::Number, literal);
+        self.make_token(TokenType::Number, literal)
     }
 
-    fn string(&mut self) -> LoxResult<()> {
+    fn string(&mut self) -> LoxResult<Token> {
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1
-            };
-            self.advance();
+            let c = self.advance();
+            if c == '\n' {
+                self.line += 1;
+                self.line_start = self.current;
+            }
         }
 
         if self.is_at_end() {
@@ -220,11 +233,9 @@ impl<'a> Scanner<'a> {
             self.advance();
 
             // Trim the surrounding quotes.
-            let value = self.source.get(self.start + 1..self.current - 1).unwrap();
-            let literal = Some(LiteralType::LString(value.to_string()));
-            self.add_token(TokenType::StringLiteral, literal);
-
-            Ok(())
+            let value = self.text(self.start + 1, self.current - 1);
+            let literal = Some(LiteralType::LString(value));
+            Ok(self.make_token(TokenType::StringLiteral, literal))
         }
     }
 
@@ -232,7 +243,7 @@ impl<'a> Scanner<'a> {
         if self.is_at_end() {
             return false;
         }
-        if self.source.chars().nth(self.current).unwrap() != expected {
+        if self.source[self.current] != expected {
             return false;
         }
 
@@ -241,14 +252,45 @@ impl<'a> Scanner<'a> {
     }
 
     fn advance(&mut self) -> char {
-        let current_value = self.current.clone();
+        let current_value = self.current;
         self.current += 1;
-        return self.source.chars().nth(current_value).unwrap();
+        self.source[current_value]
     }
 
-    fn add_token(&mut self, token_type: TokenType, literal: Option<LiteralType>) {
-        let text = self.source.get(self.start..self.current).unwrap();
-        self.tokens
-            .push(Token::new(token_type, text.to_string(), literal, self.line));
+    fn text(&self, from: usize, to: usize) -> String {
+        self.source[from..to].iter().collect()
+    }
+
+    fn make_token(&self, token_type: TokenType, literal: Option<LiteralType>) -> Token {
+        let text = self.text(self.start, self.current);
+        Token::new(token_type, text, literal, self.start_line).with_column(self.start_column)
+    }
+}
+
+impl<'a> Iterator for Scanner<'a> {
+    type Item = LoxResult<Token>;
+
+    /// Scans up to the next token, skipping whitespace and comments. The
+    /// final item is always an `EOF` token; after that the scanner is done.
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.is_at_end() {
+            self.start = self.current;
+            self.start_line = self.line;
+            self.start_column = self.start - self.line_start + 1;
+            match self.scan_token() {
+                Ok(Some(token)) => return Some(Ok(token)),
+                Ok(None) => (),
+                Err(error) => return Some(Err(error)),
+            }
+        }
+
+        if self.finished {
+            return None;
+        }
+        self.finished = true;
+
+        let column = self.current - self.line_start + 1;
+        let eof = Token::new(TokenType::EOF, "".to_string(), None, self.line);
+        Some(Ok(eof.with_column(column)))
     }
 }
@@ -1,11 +1,16 @@
 use std::{
     fmt::{self, Display},
-    ops::{Add, Deref, Div, Mul, Sub},
+    ops::{Add, Div, Mul, Sub},
 };
 
 use crate::scanner::tokenType::TokenType;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", content = "value")
+)]
 pub enum LiteralType {
     LString(String),
     LNumber(f64),
@@ -28,98 +33,137 @@ impl LiteralType {
             }
         }
     }
-    pub fn greater(self, other: LiteralType) -> Self {
-        let (left, right) = self.get_number_operands(other);
-        LiteralType::LBoolean(left > right)
+    /// Applies a binary operator, failing with a message when the operand
+    /// types don't support it.
+    pub fn apply(self, operator: TokenType, other: LiteralType) -> Result<Self, String> {
+        match operator {
+            TokenType::Greater => self.greater(other),
+            TokenType::GreaterEqual => self.greater_equal(other),
+            TokenType::Less => self.less(other),
+            TokenType::LessEqual => self.less_equal(other),
+            TokenType::EqualEqual => Ok(self.equal(other)),
+            TokenType::BangEqual => Ok(self.not_equal(other)),
+            TokenType::Minus => self - other,
+            TokenType::Slash => self / other,
+            TokenType::Star => self * other,
+            TokenType::Plus => self + other,
+            _ => panic!("invalid operator"),
+        }
+    }
+
+    /// Applies a prefix operator, failing with a message when the operand type
+    /// doesn't support it.
+    pub fn apply_unary(self, operator: TokenType) -> Result<Self, String> {
+        match operator {
+            TokenType::Minus => self.negate(),
+            TokenType::Bang => Ok(LiteralType::LBoolean(!self.is_truthy())),
+            _ => panic!("invalid operator"),
+        }
+    }
+
+    pub fn negate(self) -> Result<Self, String> {
+        match self {
+            LiteralType::LNumber(val) => Ok(LiteralType::LNumber(-val)),
+            _ => Err("Operand must be a number.".to_string()),
+        }
+    }
+
+    /// `nil` and `false` are falsey, everything else is truthy.
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, LiteralType::LNil | LiteralType::LBoolean(false))
     }
 
-    pub fn greater_equal(self, other: LiteralType) -> Self {
-        let (left, right) = self.get_number_operands(other);
-        LiteralType::LBoolean(left >= right)
+    pub fn greater(self, other: LiteralType) -> Result<Self, String> {
+        let (left, right) = self.get_number_operands(other)?;
+        Ok(LiteralType::LBoolean(left > right))
     }
 
-    pub fn less(self, other: LiteralType) -> Self {
-        let (left, right) = self.get_number_operands(other);
-        LiteralType::LBoolean(left < right)
+    pub fn greater_equal(self, other: LiteralType) -> Result<Self, String> {
+        let (left, right) = self.get_number_operands(other)?;
+        Ok(LiteralType::LBoolean(left >= right))
     }
 
-    pub fn less_equal(self, other: LiteralType) -> Self {
-        let (left, right) = self.get_number_operands(other);
-        LiteralType::LBoolean(left <= right)
+    pub fn less(self, other: LiteralType) -> Result<Self, String> {
+        let (left, right) = self.get_number_operands(other)?;
+        Ok(LiteralType::LBoolean(left < right))
     }
+
+    pub fn less_equal(self, other: LiteralType) -> Result<Self, String> {
+        let (left, right) = self.get_number_operands(other)?;
+        Ok(LiteralType::LBoolean(left <= right))
+    }
+
+    /// Lox equality never fails: values of different types are simply
+    /// unequal, and numbers follow IEEE 754, so `nan == nan` is false.
     pub fn equal(self, other: LiteralType) -> Self {
-        let (left, right) = self.get_number_operands(other);
-        LiteralType::LBoolean(left == right)
+        LiteralType::LBoolean(self == other)
     }
+
     pub fn not_equal(self, other: LiteralType) -> Self {
-        let (left, right) = self.get_number_operands(other);
-        LiteralType::LBoolean(left != right)
+        LiteralType::LBoolean(self != other)
     }
 
-    fn get_number_operands(self, other: LiteralType) -> (f64, f64) {
-        let left = match self {
-            LiteralType::LNumber(val) => val,
-            _ => panic!("Could not perform operation (left is not number)"),
-        };
-        let right = match other {
-            LiteralType::LNumber(val) => val,
-            _ => panic!("Could not perform operation (right is not number)"),
-        };
-        (left, right)
+    fn get_number_operands(self, other: LiteralType) -> Result<(f64, f64), String> {
+        match (self, other) {
+            (LiteralType::LNumber(left), LiteralType::LNumber(right)) => Ok((left, right)),
+            _ => Err("Operands must be numbers.".to_string()),
+        }
     }
 }
 
 impl Sub for LiteralType {
-    type Output = Self;
+    type Output = Result<Self, String>;
 
     fn sub(self, other: LiteralType) -> Self::Output {
-        let (left, right) = self.get_number_operands(other);
-        LiteralType::LNumber(left - right)
+        let (left, right) = self.get_number_operands(other)?;
+        Ok(LiteralType::LNumber(left - right))
     }
 }
 
 impl Div for LiteralType {
-    type Output = Self;
+    type Output = Result<Self, String>;
 
     fn div(self, other: LiteralType) -> Self::Output {
-        let (left, right) = self.get_number_operands(other);
-        LiteralType::LNumber(left / right)
+        let (left, right) = self.get_number_operands(other)?;
+        Ok(LiteralType::LNumber(left / right))
     }
 }
 
 impl Mul for LiteralType {
-    type Output = Self;
+    type Output = Result<Self, String>;
 
     fn mul(self, other: LiteralType) -> Self::Output {
-        let (left, right) = self.get_number_operands(other);
-        LiteralType::LNumber(left * right)
+        let (left, right) = self.get_number_operands(other)?;
+        Ok(LiteralType::LNumber(left * right))
     }
 }
 
 impl Add for LiteralType {
-    type Output = Self;
+    type Output = Result<Self, String>;
 
     fn add(self, other: LiteralType) -> Self::Output {
-        match self {
-            LiteralType::LNumber(left) => match other {
-                LiteralType::LNumber(right) => LiteralType::LNumber(left + right),
-                _ => panic!("cannot add a number and a different thing"),
-            },
-            LiteralType::LString(left) => match other {
-                LiteralType::LString(right) => LiteralType::LString(left + &right),
-                _ => panic!("cannot concat a string and a different thing"),
-            },
-            _ => panic!("dunno what you're trying to sum/concat but it won't work"),
+        match (self, other) {
+            (LiteralType::LNumber(left), LiteralType::LNumber(right)) => {
+                Ok(LiteralType::LNumber(left + right))
+            }
+            (LiteralType::LString(left), LiteralType::LString(right)) => {
+                Ok(LiteralType::LString(left + &right))
+            }
+            _ => Err("Operands must be two numbers or two strings.".to_string()),
         }
     }
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Token {
     pub token_type: TokenType,
     pub lexeme: String,
     pub literal: Option<LiteralType>,
     pub line: usize,
+    /// The 1-based column of the token's first character, or 0 if unknown.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub column: usize,
 }
 
 impl Token {
@@ -134,8 +178,14 @@ impl Token {
             lexeme,
             literal,
             line,
+            column: 0,
         }
     }
+
+    pub fn with_column(mut self, column: usize) -> Self {
+        self.column = column;
+        self
+    }
 }
 
 impl Display for Token {
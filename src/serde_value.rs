@@ -0,0 +1,752 @@
+//! Converts between `LoxValue` and any Rust type implementing serde's
+//! `Serialize`/`Deserialize`, so hosts can pass structured data in and out of
+//! scripts.
+//!
+//! - Numbers of every width become Lox numbers; `char`s become strings.
+//! - `None` and `()` become `nil`.
+//! - Sequences and tuples become lists.
+//! - Structs become `Record`s named after the struct, and maps become Lox
+//!   maps. Map keys must be strings, numbers, booleans or unit.
+//! - Enums are externally tagged: unit variants become their name as a
+//!   string, other variants a record with the variant name as its only field.
+//!
+//! Going back, any host object that reports its `fields` deserializes like a
+//! map, as does a Lox map. Functions can't be converted, and neither can a
+//! list, map or object that contains itself.
+//!
+//! ```
+//! use rlox::{lox::Lox, serde_value};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, PartialEq, Debug)]
+//! struct Point {
+//!     x: f64,
+//!     y: f64,
+//! }
+//!
+//! let lox = Lox::new();
+//! lox.set_global("p", serde_value::to_value(&Point { x: 1.0, y: 2.0 })?);
+//! lox.eval("p.x = p.x + p.y;")?;
+//!
+//! let p: Point = serde_value::from_value(lox.get_global("p").unwrap())?;
+//! assert_eq!(p, Point { x: 3.0, y: 2.0 });
+//! # Ok::<(), rlox::errors::Error>(())
+//! ```
+
+use std::{cell::RefCell, fmt::Display, rc::Rc};
+
+use serde::{
+    de::{self, DeserializeOwned, IntoDeserializer, Visitor},
+    ser::{self, Serialize, SerializeMap, SerializeSeq},
+    Deserializer,
+};
+
+use crate::{
+    errors::{Error, LoxResult},
+    host::Record,
+    interpreter::LoxValue,
+    map::{LoxMap, MapKey},
+    scanner::token::LiteralType,
+};
+
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> LoxResult<LoxValue> {
+    value.serialize(ValueSerializer)
+}
+
+pub fn from_value<T: DeserializeOwned>(value: LoxValue) -> LoxResult<T> {
+    T::deserialize(value)
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(message: T) -> Self {
+        Error::ConversionError(message.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(message: T) -> Self {
+        Error::ConversionError(message.to_string())
+    }
+}
+
+impl Serialize for LoxValue {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Guarded {
+            value: self,
+            serializing: &RefCell::new(vec![]),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// A value inside the lists, maps and objects in `serializing`, which it
+/// must not contain again.
+struct Guarded<'a> {
+    value: &'a LoxValue,
+    serializing: &'a RefCell<Vec<*const ()>>,
+}
+
+impl Guarded<'_> {
+    fn child<'a>(&'a self, value: &'a LoxValue) -> Guarded<'a> {
+        Guarded {
+            value,
+            serializing: self.serializing,
+        }
+    }
+
+    /// Runs `serialize` with `id` marked as being serialized, failing if it
+    /// already is.
+    fn enter<T, E: ser::Error>(
+        &self,
+        id: *const (),
+        serialize: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        if self.serializing.borrow().contains(&id) {
+            return Err(ser::Error::custom(
+                "Can't serialize a value that contains itself.",
+            ));
+        }
+        self.serializing.borrow_mut().push(id);
+        let result = serialize();
+        self.serializing.borrow_mut().pop();
+        result
+    }
+}
+
+impl Serialize for Guarded<'_> {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.value {
+            LoxValue::Literal(LiteralType::LString(string)) => serializer.serialize_str(string),
+            LoxValue::Literal(LiteralType::LNumber(number)) => serializer.serialize_f64(*number),
+            LoxValue::Literal(LiteralType::LBoolean(boolean)) => {
+                serializer.serialize_bool(*boolean)
+            }
+            LoxValue::Literal(LiteralType::LNil) => serializer.serialize_unit(),
+            LoxValue::List(list) => self.enter(Rc::as_ptr(list) as *const (), || {
+                let list = list.borrow();
+                let mut seq = serializer.serialize_seq(Some(list.len()))?;
+                for item in list.iter() {
+                    seq.serialize_element(&self.child(item))?;
+                }
+                seq.end()
+            }),
+            LoxValue::Map(map) => self.enter(Rc::as_ptr(map) as *const (), || {
+                let map = map.borrow();
+                let mut entries = serializer.serialize_map(Some(map.len()))?;
+                for (key, value) in map.iter() {
+                    entries.serialize_entry(&LoxValue::from(key.clone()), &self.child(value))?;
+                }
+                entries.end()
+            }),
+            LoxValue::HostObject(object) => match object.fields() {
+                Some(fields) => self.enter(Rc::as_ptr(object) as *const (), || {
+                    let mut map = serializer.serialize_map(Some(fields.len()))?;
+                    for (name, value) in &fields {
+                        map.serialize_entry(name, &self.child(value))?;
+                    }
+                    map.end()
+                }),
+                None => Err(ser::Error::custom(format!(
+                    "Can't serialize opaque {} object.",
+                    object.type_name()
+                ))),
+            },
+            LoxValue::NativeFunction(_) => Err(ser::Error::custom("Can't serialize a function.")),
+        }
+    }
+}
+
+struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = LoxValue;
+    type Error = Error;
+
+    type SerializeSeq = ListSerializer;
+    type SerializeTuple = ListSerializer;
+    type SerializeTupleStruct = ListSerializer;
+    type SerializeTupleVariant = VariantSerializer<ListSerializer>;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = RecordSerializer;
+    type SerializeStructVariant = VariantSerializer<RecordSerializer>;
+
+    fn serialize_bool(self, v: bool) -> LoxResult<LoxValue> {
+        Ok(LoxValue::from(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> LoxResult<LoxValue> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_i16(self, v: i16) -> LoxResult<LoxValue> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_i32(self, v: i32) -> LoxResult<LoxValue> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_i64(self, v: i64) -> LoxResult<LoxValue> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_u8(self, v: u8) -> LoxResult<LoxValue> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_u16(self, v: u16) -> LoxResult<LoxValue> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_u32(self, v: u32) -> LoxResult<LoxValue> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_u64(self, v: u64) -> LoxResult<LoxValue> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f32(self, v: f32) -> LoxResult<LoxValue> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> LoxResult<LoxValue> {
+        Ok(LoxValue::from(v))
+    }
+
+    fn serialize_char(self, v: char) -> LoxResult<LoxValue> {
+        Ok(LoxValue::from(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> LoxResult<LoxValue> {
+        Ok(LoxValue::from(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> LoxResult<LoxValue> {
+        Ok(LoxValue::list(
+            v.iter().map(|byte| LoxValue::from(*byte as f64)).collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> LoxResult<LoxValue> {
+        Ok(LoxValue::nil())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> LoxResult<LoxValue> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> LoxResult<LoxValue> {
+        Ok(LoxValue::nil())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> LoxResult<LoxValue> {
+        Ok(LoxValue::nil())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> LoxResult<LoxValue> {
+        Ok(LoxValue::from(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> LoxResult<LoxValue> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> LoxResult<LoxValue> {
+        let fields = vec![(variant.to_string(), to_value(value)?)];
+        Ok(LoxValue::host_object(Record::new(name, fields)))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> LoxResult<ListSerializer> {
+        Ok(ListSerializer {
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> LoxResult<ListSerializer> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> LoxResult<ListSerializer> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> LoxResult<VariantSerializer<ListSerializer>> {
+        Ok(VariantSerializer {
+            name,
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> LoxResult<MapSerializer> {
+        Ok(MapSerializer::default())
+    }
+
+    fn serialize_struct(self, name: &'static str, _len: usize) -> LoxResult<RecordSerializer> {
+        Ok(RecordSerializer::new(name))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> LoxResult<VariantSerializer<RecordSerializer>> {
+        Ok(VariantSerializer {
+            name,
+            variant,
+            inner: RecordSerializer::new(variant),
+        })
+    }
+}
+
+struct ListSerializer {
+    items: Vec<LoxValue>,
+}
+
+impl ListSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> LoxResult<()> {
+        self.items.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn finish(self) -> LoxValue {
+        LoxValue::list(self.items)
+    }
+}
+
+impl ser::SerializeSeq for ListSerializer {
+    type Ok = LoxValue;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> LoxResult<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> LoxResult<LoxValue> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for ListSerializer {
+    type Ok = LoxValue;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> LoxResult<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> LoxResult<LoxValue> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for ListSerializer {
+    type Ok = LoxValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> LoxResult<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> LoxResult<LoxValue> {
+        Ok(self.finish())
+    }
+}
+
+#[derive(Default)]
+struct MapSerializer {
+    map: LoxMap,
+    next_key: Option<MapKey>,
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = LoxValue;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> LoxResult<()> {
+        let key = MapKey::try_from(to_value(key)?).map_err(Error::ConversionError)?;
+        self.next_key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> LoxResult<()> {
+        let key = self.next_key.take().unwrap();
+        self.map.insert(key, to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> LoxResult<LoxValue> {
+        Ok(LoxValue::map(self.map))
+    }
+}
+
+struct RecordSerializer {
+    type_name: &'static str,
+    fields: Vec<(String, LoxValue)>,
+}
+
+impl RecordSerializer {
+    fn new(type_name: &'static str) -> Self {
+        RecordSerializer {
+            type_name,
+            fields: vec![],
+        }
+    }
+
+    fn finish(self) -> LoxValue {
+        LoxValue::host_object(Record::new(self.type_name, self.fields))
+    }
+}
+
+impl ser::SerializeStruct for RecordSerializer {
+    type Ok = LoxValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> LoxResult<()> {
+        self.fields.push((key.to_string(), to_value(value)?));
+        Ok(())
+    }
+
+    fn end(self) -> LoxResult<LoxValue> {
+        Ok(self.finish())
+    }
+}
+
+/// Wraps the contents of a tuple or struct variant in a record holding a
+/// single field named after the variant.
+struct VariantSerializer<S> {
+    name: &'static str,
+    variant: &'static str,
+    inner: S,
+}
+
+impl<S> VariantSerializer<S> {
+    fn finish(name: &'static str, variant: &'static str, value: LoxValue) -> LoxValue {
+        let fields = vec![(variant.to_string(), value)];
+        LoxValue::host_object(Record::new(name, fields))
+    }
+}
+
+impl ser::SerializeTupleVariant for VariantSerializer<ListSerializer> {
+    type Ok = LoxValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> LoxResult<()> {
+        self.inner.push(value)
+    }
+
+    fn end(self) -> LoxResult<LoxValue> {
+        Ok(Self::finish(self.name, self.variant, self.inner.finish()))
+    }
+}
+
+impl ser::SerializeStructVariant for VariantSerializer<RecordSerializer> {
+    type Ok = LoxValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> LoxResult<()> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> LoxResult<LoxValue> {
+        Ok(Self::finish(self.name, self.variant, self.inner.finish()))
+    }
+}
+
+/// Integers arrive as Lox numbers, so they are accepted only when the number
+/// is whole and fits the target type. `MAX as f64` rounds up to a power of two
+/// for 64-bit types, so the upper bound is that power of two, computed exactly
+/// and excluded.
+macro_rules! deserialize_integer {
+    ($($method:ident => $visit:ident($target:ty)),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> LoxResult<V::Value> {
+                let end = (<$target>::MAX / 2 + 1) as f64 * 2.0;
+                match self.value {
+                    LoxValue::Literal(LiteralType::LNumber(number))
+                        if number.fract() == 0.0
+                            && number >= <$target>::MIN as f64
+                            && number < end =>
+                    {
+                        visitor.$visit(number as $target)
+                    }
+                    other => Err(de::Error::custom(format!(
+                        "Expected an integer but got {}.",
+                        other.stringify()
+                    ))),
+                }
+            }
+        )*
+    };
+}
+
+/// Hands each method on to a `Deserializing` of the value.
+macro_rules! deserialize_guarded {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> LoxResult<V::Value> {
+                Deserializing::new(self).$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for LoxValue {
+    type Error = Error;
+
+    deserialize_guarded! {
+        deserialize_any deserialize_option
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> LoxResult<V::Value> {
+        Deserializing::new(self).deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> LoxResult<V::Value> {
+        Deserializing::new(self).deserialize_enum(name, variants, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool f32 f64 char str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for LoxValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// A value inside the lists, maps and objects in `deserializing`, which it
+/// must not contain again. The deserializing counterpart of `Guarded`.
+struct Deserializing {
+    value: LoxValue,
+    deserializing: Rc<RefCell<Vec<*const ()>>>,
+}
+
+impl Deserializing {
+    fn new(value: LoxValue) -> Self {
+        Deserializing {
+            value,
+            deserializing: Rc::default(),
+        }
+    }
+
+    fn child(&self, value: LoxValue) -> Deserializing {
+        Deserializing {
+            value,
+            deserializing: Rc::clone(&self.deserializing),
+        }
+    }
+
+    /// Runs `deserialize` with `id` marked as being deserialized, failing if
+    /// it already is.
+    fn enter<T>(&self, id: *const (), deserialize: impl FnOnce() -> LoxResult<T>) -> LoxResult<T> {
+        if self.deserializing.borrow().contains(&id) {
+            return Err(de::Error::custom(
+                "Can't deserialize a value that contains itself.",
+            ));
+        }
+        self.deserializing.borrow_mut().push(id);
+        let result = deserialize();
+        self.deserializing.borrow_mut().pop();
+        result
+    }
+}
+
+impl<'de> Deserializer<'de> for Deserializing {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> LoxResult<V::Value> {
+        match &self.value {
+            LoxValue::Literal(LiteralType::LString(string)) => visitor.visit_str(string),
+            LoxValue::Literal(LiteralType::LNumber(number)) => visitor.visit_f64(*number),
+            LoxValue::Literal(LiteralType::LBoolean(boolean)) => visitor.visit_bool(*boolean),
+            LoxValue::Literal(LiteralType::LNil) => visitor.visit_unit(),
+            LoxValue::List(list) => self.enter(Rc::as_ptr(list) as *const (), || {
+                let items: Vec<Deserializing> = list
+                    .borrow()
+                    .iter()
+                    .map(|item| self.child(item.clone()))
+                    .collect();
+                visitor.visit_seq(de::value::SeqDeserializer::new(items.into_iter()))
+            }),
+            LoxValue::Map(map) => self.enter(Rc::as_ptr(map) as *const (), || {
+                let entries: Vec<(LoxValue, Deserializing)> = map
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| (LoxValue::from(key.clone()), self.child(value.clone())))
+                    .collect();
+                visitor.visit_map(de::value::MapDeserializer::new(entries.into_iter()))
+            }),
+            LoxValue::HostObject(object) => match object.fields() {
+                Some(fields) => self.enter(Rc::as_ptr(object) as *const (), || {
+                    let fields: Vec<(String, Deserializing)> = fields
+                        .into_iter()
+                        .map(|(name, value)| (name, self.child(value)))
+                        .collect();
+                    visitor.visit_map(de::value::MapDeserializer::new(fields.into_iter()))
+                }),
+                None => Err(de::Error::custom(format!(
+                    "Can't deserialize opaque {} object.",
+                    object.type_name()
+                ))),
+            },
+            LoxValue::NativeFunction(_) => Err(de::Error::custom("Can't deserialize a function.")),
+        }
+    }
+
+    deserialize_integer! {
+        deserialize_i8 => visit_i8(i8),
+        deserialize_i16 => visit_i16(i16),
+        deserialize_i32 => visit_i32(i32),
+        deserialize_i64 => visit_i64(i64),
+        deserialize_u8 => visit_u8(u8),
+        deserialize_u16 => visit_u16(u16),
+        deserialize_u32 => visit_u32(u32),
+        deserialize_u64 => visit_u64(u64),
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> LoxResult<V::Value> {
+        if self.value.is_nil() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> LoxResult<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> LoxResult<V::Value> {
+        match &self.value {
+            LoxValue::Literal(LiteralType::LString(variant)) => {
+                visitor.visit_enum(variant.clone().into_deserializer())
+            }
+            LoxValue::HostObject(object) => match object.fields().as_deref() {
+                Some([(variant, value)]) => visitor.visit_enum(EnumDeserializer {
+                    variant: variant.clone(),
+                    value: self.child(value.clone()),
+                }),
+                _ => Err(de::Error::custom(
+                    "Expected a record with exactly one field naming the variant.",
+                )),
+            },
+            other => Err(de::Error::custom(format!(
+                "Expected an enum variant but got {}.",
+                other.type_name()
+            ))),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool f32 f64 char str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Deserializing {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: Deserializing,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    type Variant = Deserializing;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> LoxResult<(V::Value, Deserializing)> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Deserializing {
+    type Error = Error;
+
+    fn unit_variant(self) -> LoxResult<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> LoxResult<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> LoxResult<V::Value> {
+        self.deserialize_any(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> LoxResult<V::Value> {
+        self.deserialize_any(visitor)
+    }
+}
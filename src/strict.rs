@@ -0,0 +1,189 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use crate::{
+    ast::{Expr, Stmt, StmtVisitor, Visitor},
+    errors::{Error, LoxResult},
+    scanner::token::{LiteralType, Token},
+};
+
+/// Strict mode: rejects programs that read a variable declared without an
+/// initializer before anything has been assigned to it, e.g.
+/// `var a; print a;`. Without strict mode such a read just yields `nil`.
+///
+/// Only variables declared in the checked program are tracked, so globals
+/// defined elsewhere (an earlier REPL line, the host) are left to runtime.
+/// A loop body may run no times at all, so assignments made only inside one
+/// don't count once the loop is over.
+pub struct StrictChecker {
+    /// The variables declared in each enclosing scope, innermost last, and
+    /// whether each has been assigned yet.
+    scopes: RefCell<Vec<HashMap<String, bool>>>,
+    error: RefCell<Option<Error>>,
+}
+
+impl StrictChecker {
+    pub fn new() -> Self {
+        StrictChecker {
+            scopes: RefCell::new(vec![HashMap::new()]),
+            error: RefCell::new(None),
+        }
+    }
+
+    pub fn check(&self, statements: &[Stmt]) -> LoxResult<()> {
+        for statement in statements {
+            self.handle_stmt(statement);
+            if let Some(error) = self.error.take() {
+                return Err(error);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl StrictChecker {
+    fn declare(&self, name: &str, assigned: bool) {
+        let mut scopes = self.scopes.borrow_mut();
+        let scope = scopes.last_mut().expect("the global scope is never popped");
+        scope.insert(name.to_string(), assigned);
+    }
+
+    /// Marks the innermost `name` as assigned, if the program declared it.
+    fn assign(&self, name: &str) {
+        let mut scopes = self.scopes.borrow_mut();
+        if let Some(assigned) = scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(name))
+        {
+            *assigned = true;
+        }
+    }
+
+    fn is_unassigned(&self, name: &str) -> bool {
+        let scopes = self.scopes.borrow();
+        scopes.iter().rev().find_map(|scope| scope.get(name)) == Some(&false)
+    }
+
+    /// Runs `check` in a new scope, dropped again afterwards.
+    fn in_scope(&self, check: impl FnOnce()) {
+        self.scopes.borrow_mut().push(HashMap::new());
+        check();
+        self.scopes.borrow_mut().pop();
+    }
+}
+
+impl Default for StrictChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Visitor<()> for StrictChecker {
+    fn visit_assign_expr(&self, token: &Token, expr: &Expr) {
+        self.handle_expr(expr);
+        self.assign(&token.lexeme);
+    }
+
+    fn visit_binary_expr(&self, left: &Expr, _token: &Token, right: &Expr) {
+        self.handle_expr(left);
+        self.handle_expr(right);
+    }
+
+    fn visit_call_expr(&self, callee: &Expr, _paren: &Token, arguments: &[Expr]) {
+        self.handle_expr(callee);
+        for argument in arguments {
+            self.handle_expr(argument);
+        }
+    }
+
+    fn visit_get_expr(&self, object: &Expr, _name: &Token) {
+        self.handle_expr(object);
+    }
+
+    fn visit_grouping_expr(&self, expr: &Expr) {
+        self.handle_expr(expr);
+    }
+
+    fn visit_index_expr(&self, object: &Expr, _bracket: &Token, index: &Expr) {
+        self.handle_expr(object);
+        self.handle_expr(index);
+    }
+
+    fn visit_list_expr(&self, items: &[Expr]) {
+        for item in items {
+            self.handle_expr(item);
+        }
+    }
+
+    fn visit_literal_expr(&self, _literal: &LiteralType) {}
+
+    fn visit_map_expr(&self, _brace: &Token, entries: &[(Expr, Expr)]) {
+        for (key, value) in entries {
+            self.handle_expr(key);
+            self.handle_expr(value);
+        }
+    }
+
+    fn visit_set_expr(&self, object: &Expr, _name: &Token, value: &Expr) {
+        self.handle_expr(object);
+        self.handle_expr(value);
+    }
+
+    fn visit_set_index_expr(&self, object: &Expr, _bracket: &Token, index: &Expr, value: &Expr) {
+        self.handle_expr(object);
+        self.handle_expr(index);
+        self.handle_expr(value);
+    }
+
+    fn visit_unary_expr(&self, _token: &Token, expr: &Expr) {
+        self.handle_expr(expr);
+    }
+
+    fn visit_variable_expr(&self, token: &Token) {
+        if self.is_unassigned(&token.lexeme) {
+            self.error.borrow_mut().get_or_insert(Error::StrictError(
+                token.line,
+                format!("Variable '{}' is read before being assigned.", token.lexeme),
+            ));
+        }
+    }
+}
+
+impl StmtVisitor<()> for StrictChecker {
+    fn visit_block_stmt(&self, statements: &[Stmt]) {
+        self.in_scope(|| {
+            for statement in statements {
+                self.handle_stmt(statement);
+            }
+        });
+    }
+
+    fn visit_expression_stmt(&self, expr: &Expr) {
+        self.handle_expr(expr);
+    }
+
+    fn visit_for_in_stmt(&self, name: &Token, iterable: &Expr, body: &Stmt) {
+        self.handle_expr(iterable);
+        let before = self.scopes.borrow().clone();
+        self.in_scope(|| {
+            self.declare(&name.lexeme, true);
+            self.handle_stmt(body);
+        });
+        *self.scopes.borrow_mut() = before;
+    }
+
+    fn visit_print_stmt(&self, expr: &Expr) {
+        self.handle_expr(expr);
+    }
+
+    fn visit_var_stmt(&self, token: &Token, expr: Option<&Expr>) {
+        match expr {
+            Some(initializer) => {
+                self.handle_expr(initializer);
+                self.declare(&token.lexeme, true);
+            }
+            None => self.declare(&token.lexeme, false),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Methods on string values, e.g. `"abc".upper()`. Indexes and lengths count
+//! Unicode characters, not bytes.
+//!
+//! - `len()` is the number of characters.
+//! - `substring(start, end)` is the characters from `start` up to but not
+//!   including `end`.
+//! - `indexOf(part)` is the index where `part` first appears, or -1.
+//! - `split(separator)` is a list of the pieces between separators; an empty
+//!   separator splits into single characters.
+//! - `join(list)` strings the list's items together with this string
+//!   between them, so `", ".join(items)`.
+//! - `trim()`, `upper()` and `lower()`.
+//! - `replace(from, to)` replaces every occurrence of `from`.
+//! - `startsWith(prefix)` and `endsWith(suffix)`.
+//! - `repeat(count)` is the string repeated `count` times. It fails rather
+//!   than build a string over the allocation limit, or over 256 MiB.
+//!
+//! Every string or list a method builds is charged to the allocation limit.
+//! - `charCodeAt(index)` is the Unicode code point at `index`; the global
+//!   `fromCharCode(code)` turns a code point back into a string.
+
+use std::rc::Rc;
+
+use crate::{
+    allocations::Allocations,
+    interpreter::LoxValue,
+    native::{NativeFunction, NativeResult},
+    scanner::token::LiteralType,
+};
+
+/// The longest string `repeat` builds, even without an allocation limit.
+const MAX_REPEATED_LEN: usize = 256 * 1024 * 1024;
+
+/// The method `name` of `string`, bound to it, or `None` if strings have no
+/// such method. The values methods build are charged to `allocations`.
+pub(crate) fn method(string: &str, name: &str, allocations: &Rc<Allocations>) -> Option<LoxValue> {
+    let arity = match name {
+        "len" | "trim" | "upper" | "lower" => 0,
+        "indexOf" | "split" | "join" | "startsWith" | "endsWith" | "repeat" | "charCodeAt" => 1,
+        "substring" | "replace" => 2,
+        _ => return None,
+    };
+
+    let string = string.to_string();
+    let method = name.to_string();
+    let allocations = Rc::clone(allocations);
+    let native = NativeFunction::new(name, arity, move |args| {
+        call(&string, &method, args, &allocations)
+    });
+    Some(LoxValue::NativeFunction(Rc::new(native)))
+}
+
+fn call(string: &str, method: &str, args: &[LoxValue], allocations: &Allocations) -> NativeResult {
+    let value = match method {
+        "len" => LoxValue::from(string.chars().count() as f64),
+        "trim" => allocations.charged(LoxValue::from(string.trim()))?,
+        "upper" => allocations.charged(LoxValue::from(string.to_uppercase()))?,
+        "lower" => allocations.charged(LoxValue::from(string.to_lowercase()))?,
+        "substring" => {
+            let start = index(method, &args[0])?;
+            let end = index(method, &args[1])?;
+            if start > end || end > string.chars().count() {
+                return Err("Substring range out of bounds.".into());
+            }
+            let substring: String = string.chars().skip(start).take(end - start).collect();
+            allocations.charged(LoxValue::from(substring))?
+        }
+        "indexOf" => {
+            let part = text(method, &args[0])?;
+            let found = string
+                .find(&part)
+                .map(|byte| string[..byte].chars().count() as f64);
+            LoxValue::from(found.unwrap_or(-1.0))
+        }
+        "split" => {
+            let separator = text(method, &args[0])?;
+            let pieces: Vec<LoxValue> = if separator.is_empty() {
+                string
+                    .chars()
+                    .map(|c| LoxValue::from(c.to_string()))
+                    .collect()
+            } else {
+                string.split(&separator).map(LoxValue::from).collect()
+            };
+            allocations.charged(LoxValue::list(pieces))?
+        }
+        "join" => match &args[0] {
+            LoxValue::List(items) => {
+                let items: Vec<String> =
+                    items.borrow().iter().map(|item| item.stringify()).collect();
+                allocations.charged(LoxValue::from(items.join(string)))?
+            }
+            other => return Err(expected(method, "a list", other).into()),
+        },
+        "replace" => {
+            let from = text(method, &args[0])?;
+            let to = text(method, &args[1])?;
+            if from.is_empty() {
+                return Err("Can't replace an empty string.".into());
+            }
+            allocations.charged(LoxValue::from(string.replace(&from, &to)))?
+        }
+        "startsWith" => LoxValue::from(string.starts_with(&text(method, &args[0])?)),
+        "endsWith" => LoxValue::from(string.ends_with(&text(method, &args[0])?)),
+        "repeat" => {
+            let count = index(method, &args[0])?;
+            let len = string
+                .len()
+                .checked_mul(count)
+                .filter(|&len| len <= MAX_REPEATED_LEN)
+                .ok_or("Repeated string is too long.")?;
+            allocations.check(len)?;
+            allocations.charged(LoxValue::from(string.repeat(count)))?
+        }
+        "charCodeAt" => {
+            let position = index(method, &args[0])?;
+            match string.chars().nth(position) {
+                Some(c) => LoxValue::from(c as u32 as f64),
+                None => return Err("Index out of bounds.".into()),
+            }
+        }
+        _ => unreachable!("no string method '{}'", method),
+    };
+
+    Ok(value)
+}
+
+/// Turns a code point back into a one-character string; the inverse of
+/// `charCodeAt`.
+pub(crate) fn from_char_code(args: &[LoxValue]) -> NativeResult {
+    let code = index("fromCharCode", &args[0])?;
+    let character = u32::try_from(code)
+        .ok()
+        .and_then(char::from_u32)
+        .ok_or_else(|| format!("{} is not a valid character code.", code))?;
+    Ok(LoxValue::from(character.to_string()))
+}
+
+fn text(method: &str, value: &LoxValue) -> Result<String, String> {
+    match value {
+        LoxValue::Literal(LiteralType::LString(string)) => Ok(string.clone()),
+        other => Err(expected(method, "a string", other)),
+    }
+}
+
+/// A non-negative whole number, as used for indexes and counts.
+pub(crate) fn index(method: &str, value: &LoxValue) -> Result<usize, String> {
+    match value {
+        LoxValue::Literal(LiteralType::LNumber(number))
+            if number.fract() == 0.0 && *number >= 0.0 && *number <= u32::MAX as f64 =>
+        {
+            Ok(*number as usize)
+        }
+        other => Err(format!(
+            "{}() expects a non-negative integer but got {}.",
+            method,
+            other.stringify()
+        )),
+    }
+}
+
+pub(crate) fn expected(method: &str, kind: &str, got: &LoxValue) -> String {
+    format!("{}() expects {} but got {}.", method, kind, got.type_name())
+}
@@ -0,0 +1,80 @@
+//! Natives that reach outside the interpreter: files, the clock, randomness
+//! and environment variables. Each one checks the interpreter's
+//! `Capabilities` on every call and fails if the script lacks permission.
+
+use std::{
+    cell::RefCell,
+    collections::hash_map::RandomState,
+    env, fs,
+    hash::{BuildHasher, Hasher},
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    allocations::Allocations,
+    capabilities::Capabilities,
+    interpreter::{Interpreter, LoxValue},
+};
+
+pub(crate) fn define(
+    interpreter: &Interpreter,
+    capabilities: &Rc<RefCell<Capabilities>>,
+    allocations: &Rc<Allocations>,
+) {
+    let allowed = capabilities.clone();
+    interpreter.define_native("clock", 0, move |_| {
+        allowed.borrow().check_clock()?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|error| error.to_string())?;
+        Ok(LoxValue::from(now.as_secs_f64()))
+    });
+
+    let allowed = capabilities.clone();
+    interpreter.define_native("random", 0, move |_| {
+        allowed.borrow().check_random()?;
+        // Every RandomState is freshly keyed, which is random enough for
+        // scripts and avoids pulling in a dependency.
+        let bits = RandomState::new().build_hasher().finish();
+        Ok(LoxValue::from((bits >> 11) as f64 / (1u64 << 53) as f64))
+    });
+
+    let allowed = capabilities.clone();
+    let charge = allocations.clone();
+    interpreter.define_native("getenv", 1, move |args| {
+        allowed.borrow().check_env()?;
+        let name = string_argument(&args[0], "getenv")?;
+        Ok(charge.charged(env::var(name).ok().into())?)
+    });
+
+    let allowed = capabilities.clone();
+    let charge = allocations.clone();
+    interpreter.define_native("readFile", 1, move |args| {
+        let path = string_argument(&args[0], "readFile")?;
+        let path = allowed.borrow().check_read(&path)?;
+        let contents = fs::read_to_string(&path)
+            .map_err(|error| format!("Could not read '{}': {}.", path.display(), error))?;
+        Ok(charge.charged(LoxValue::from(contents))?)
+    });
+
+    let allowed = capabilities.clone();
+    interpreter.define_native("writeFile", 2, move |args| {
+        let path = string_argument(&args[0], "writeFile")?;
+        let contents = string_argument(&args[1], "writeFile")?;
+        let path = allowed.borrow().check_write(&path)?;
+        fs::write(&path, contents)
+            .map_err(|error| format!("Could not write '{}': {}.", path.display(), error))?;
+        Ok(LoxValue::nil())
+    });
+}
+
+fn string_argument(value: &LoxValue, function: &str) -> Result<String, String> {
+    String::try_from(value.clone()).map_err(|_| {
+        format!(
+            "{}() expects a string but got {}.",
+            function,
+            value.type_name()
+        )
+    })
+}
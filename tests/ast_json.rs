@@ -0,0 +1,77 @@
+#![cfg(feature = "serde")]
+
+use rlox::{
+    ast::{Expr, Stmt},
+    ast_json::{from_json, to_json},
+    ast_printer::AstPrinter,
+    parser::Parser,
+    scanner::Scanner,
+};
+
+fn parse(source: &str) -> Vec<Stmt> {
+    Parser::new(Scanner::new(source.to_string()))
+        .parse()
+        .unwrap_or_else(|error| panic!("{}", error))
+}
+
+fn print(statements: &[Stmt]) -> Vec<String> {
+    let printer = AstPrinter::new();
+    statements.iter().map(|stmt| printer.print(stmt)).collect()
+}
+
+/// The example from the module docs, for `print 2 + a;`.
+const DOCUMENTED: &str = r#"[
+  {
+    "type": "Print",
+    "fields": {
+      "type": "Binary",
+      "fields": [
+        { "type": "Literal", "fields": { "type": "LNumber", "value": 2.0 } },
+        { "token_type": "Plus", "lexeme": "+", "literal": null, "line": 1, "column": 9 },
+        { "type": "Variable", "fields": { "token_type": "Identifier", "lexeme": "a", "literal": null, "line": 1, "column": 11 } }
+      ]
+    }
+  }
+]"#;
+
+#[test]
+fn programs_encode_to_the_documented_schema() {
+    let json = to_json(&parse("print 2 + a;")).unwrap();
+
+    let encoded: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let documented: serde_json::Value = serde_json::from_str(DOCUMENTED).unwrap();
+    assert_eq!(encoded, documented);
+}
+
+#[test]
+fn the_documented_schema_decodes() {
+    let statements = from_json(DOCUMENTED).unwrap();
+    assert_eq!(print(&statements), ["(print (+ 2 a))"]);
+}
+
+#[test]
+fn programs_survive_a_round_trip() {
+    let statements = parse(
+        "var m = {\"a\": [1, 2]};\n\
+         m[\"b\"] = -m[\"a\"][0];\n\
+         for (x in m.keys()) { print x == nil; }\n\
+         o.f = \"s\";",
+    );
+
+    let decoded = from_json(&to_json(&statements).unwrap()).unwrap();
+    assert_eq!(print(&decoded), print(&statements));
+}
+
+#[test]
+fn columns_default_to_zero() {
+    let json = r#"[{ "type": "Expression", "fields": { "type": "Variable",
+        "fields": { "token_type": "Identifier", "lexeme": "a", "literal": null, "line": 1 } } }]"#;
+
+    match &from_json(json).unwrap()[..] {
+        [Stmt::Expression(expr)] => match &**expr {
+            Expr::Variable(token) => assert_eq!(token.column, 0),
+            _ => panic!("expected a variable"),
+        },
+        _ => panic!("expected one expression statement"),
+    }
+}
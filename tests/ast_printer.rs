@@ -0,0 +1,86 @@
+use std::{fs, process::Command};
+
+use rlox::{ast_printer::AstPrinter, parser::Parser, scanner::Scanner};
+
+/// Each statement of `source` as an S-expression, one per line.
+fn print(source: &str) -> String {
+    let statements = Parser::new(Scanner::new(source.to_string()))
+        .parse()
+        .unwrap_or_else(|error| panic!("{}", error));
+    let printer = AstPrinter::new();
+    statements
+        .iter()
+        .map(|statement| printer.print(statement))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn prints_expressions() {
+    assert_eq!(print("-123 * (45.67);"), "(; (* (- 123) (group 45.67)))");
+    assert_eq!(print("!true == nil;"), "(; (== (! true) nil))");
+    assert_eq!(print("\"a\" + b;"), "(; (+ \"a\" b))");
+}
+
+#[test]
+fn prints_calls_properties_and_assignments() {
+    assert_eq!(print("f(1, g());"), "(; (call f 1 (call g)))");
+    assert_eq!(print("a.b.c = d;"), "(; (set c (get b a) d))");
+    assert_eq!(print("x = y = 1;"), "(; (= x (= y 1)))");
+}
+
+#[test]
+fn prints_declarations_and_statements() {
+    assert_eq!(
+        print("var a; var b = 1; print a;"),
+        "(var a)\n(var b 1)\n(print a)"
+    );
+    assert_eq!(
+        print("{ var a = 1; print a; }"),
+        "(block (var a 1) (print a))"
+    );
+}
+
+#[test]
+fn prints_lists_maps_and_indexing() {
+    assert_eq!(print("[];"), "(; (list))");
+    assert_eq!(print("[1, [2]];"), "(; (list 1 (list 2)))");
+    assert_eq!(print("({});"), "(; (group (map)))");
+    assert_eq!(
+        print("({\"a\": 1, 2: b});"),
+        "(; (group (map \"a\" 1 2 b)))"
+    );
+    assert_eq!(print("xs[0][1];"), "(; (index (index xs 0) 1))");
+    assert_eq!(
+        print("xs[i] = m[\"k\"];"),
+        "(; (set-index xs i (index m \"k\")))"
+    );
+}
+
+#[test]
+fn prints_for_in_loops() {
+    assert_eq!(print("for (x in xs) print x;"), "(for-in x xs (print x))");
+    assert_eq!(
+        print("for (i in range(0, 3)) { print i; }"),
+        "(for-in i (call range 0 3) (block (print i)))"
+    );
+}
+
+#[test]
+fn dump_ast_prints_the_tree_instead_of_running() {
+    let path = std::env::temp_dir().join(format!("rlox-dump-ast-{}.lox", std::process::id()));
+    fs::write(&path, "var xs = [1, 2];\nfor (x in xs) print x * 2;\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .arg("--dump-ast")
+        .arg(&path)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "(var xs (list 1 2))\n(for-in x xs (print (* x 2)))\n"
+    );
+}
@@ -0,0 +1,207 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use rlox::{capabilities::Capabilities, interpreter::LoxValue, lox::Lox};
+
+mod common;
+use common::{error_in, temp_path};
+
+/// A fresh directory holding `secret.txt`, unique to each test.
+fn sandbox(name: &str) -> PathBuf {
+    let dir = temp_path(&format!("capabilities-{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("secret.txt"), "hunter2").unwrap();
+    dir
+}
+
+fn lox_with(capabilities: Capabilities) -> Lox {
+    let lox = Lox::new();
+    lox.interpreter().set_capabilities(capabilities);
+    lox
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+#[test]
+fn scripts_start_with_no_capabilities() {
+    let dir = sandbox("none");
+    let path = dir.join("secret.txt");
+    let lox = Lox::new();
+    lox.set_global("path", path_str(&path));
+
+    for (source, capability) in [
+        ("clock();", "clock"),
+        ("random();", "random"),
+        ("getenv(\"HOME\");", "env"),
+    ] {
+        assert_eq!(
+            error_in(&lox, source),
+            format!("Script lacks the '{}' capability.", capability)
+        );
+    }
+    assert_eq!(
+        error_in(&lox, "readFile(path);"),
+        format!("Reading '{}' is not allowed.", path_str(&path))
+    );
+    assert_eq!(
+        error_in(&lox, "writeFile(path, \"x\");"),
+        format!("Writing '{}' is not allowed.", path_str(&path))
+    );
+}
+
+#[test]
+fn granted_capabilities_work() {
+    let lox = lox_with(
+        Capabilities::none()
+            .allow_clock()
+            .allow_random()
+            .allow_env(),
+    );
+
+    assert!(f64::try_from(lox.eval("clock();").unwrap()).unwrap() > 0.0);
+    let random = f64::try_from(lox.eval("random();").unwrap()).unwrap();
+    assert!((0.0..1.0).contains(&random));
+    assert_eq!(
+        lox.eval("getenv(\"RLOX_SURELY_UNSET\");").unwrap(),
+        LoxValue::nil()
+    );
+}
+
+#[test]
+fn reads_are_confined_to_their_roots() {
+    let dir = sandbox("read");
+    let inside = dir.join("inside");
+    fs::create_dir(&inside).unwrap();
+    fs::write(inside.join("note.txt"), "hello").unwrap();
+
+    let outside = dir.join("secret.txt");
+    let escape = inside.join("../secret.txt");
+    let lox = lox_with(Capabilities::none().allow_read(&inside));
+    lox.set_global("inside", path_str(&inside.join("note.txt")));
+    lox.set_global("outside", path_str(&outside));
+    lox.set_global("escape", path_str(&escape));
+
+    assert_eq!(
+        lox.eval("readFile(inside);").unwrap(),
+        LoxValue::from("hello")
+    );
+    assert_eq!(
+        error_in(&lox, "readFile(outside);"),
+        format!("Reading '{}' is not allowed.", path_str(&outside))
+    );
+    assert_eq!(
+        error_in(&lox, "readFile(escape);"),
+        format!("Reading '{}' is not allowed.", path_str(&escape))
+    );
+}
+
+#[test]
+fn writes_are_confined_to_their_roots() {
+    let dir = sandbox("write");
+    let path = dir.join("out.txt");
+    let elsewhere = dir.join("../rlox-escape.txt");
+    let lox = lox_with(Capabilities::none().allow_write(&dir));
+    lox.set_global("path", path_str(&path));
+    lox.set_global("elsewhere", path_str(&elsewhere));
+
+    lox.eval("writeFile(path, \"saved\");").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "saved");
+
+    // Writing doesn't imply reading.
+    assert_eq!(
+        error_in(&lox, "readFile(path);"),
+        format!("Reading '{}' is not allowed.", path_str(&path))
+    );
+    assert_eq!(
+        error_in(&lox, "writeFile(elsewhere, \"x\");"),
+        format!("Writing '{}' is not allowed.", path_str(&elsewhere))
+    );
+}
+
+#[test]
+fn paths_outside_the_roots_are_denied_whether_or_not_they_exist() {
+    let dir = sandbox("existence");
+    let inside = dir.join("inside");
+    fs::create_dir(&inside).unwrap();
+
+    let lox = lox_with(
+        Capabilities::none()
+            .allow_read(&inside)
+            .allow_write(&inside),
+    );
+    let missing = dir.join("missing.txt");
+    let existing = dir.join("secret.txt");
+    let nowhere = dir.join("no/such/dir/x.txt");
+    let absent = inside.join("absent.txt");
+    lox.set_global("missing", path_str(&missing));
+    lox.set_global("existing", path_str(&existing));
+    lox.set_global("nowhere", path_str(&nowhere));
+    lox.set_global("absent", path_str(&absent));
+
+    assert_eq!(
+        error_in(&lox, "readFile(missing);"),
+        format!("Reading '{}' is not allowed.", path_str(&missing))
+    );
+    assert_eq!(
+        error_in(&lox, "readFile(existing);"),
+        format!("Reading '{}' is not allowed.", path_str(&existing))
+    );
+    assert_eq!(
+        error_in(&lox, "writeFile(nowhere, \"x\");"),
+        format!("Writing '{}' is not allowed.", path_str(&nowhere))
+    );
+
+    // Inside the roots, a missing file is reported as such.
+    let error = error_in(&lox, "readFile(absent);");
+    let opening = format!("Could not open '{}': ", path_str(&absent));
+    assert!(error.starts_with(&opening), "{}", error);
+}
+
+#[test]
+fn capabilities_can_change_between_runs() {
+    let lox = Lox::new();
+    assert_eq!(
+        error_in(&lox, "clock();"),
+        "Script lacks the 'clock' capability."
+    );
+
+    lox.interpreter()
+        .set_capabilities(Capabilities::none().allow_clock());
+    assert!(lox.eval("clock();").is_ok());
+}
+
+#[test]
+fn the_cli_grants_reads_with_a_flag() {
+    let dir = sandbox("cli");
+    let script = dir.join("script.lox");
+    let secret = dir.join("secret.txt");
+    fs::write(
+        &script,
+        format!("print readFile(\"{}\");", secret.to_str().unwrap()),
+    )
+    .unwrap();
+
+    let run = |args: &[String]| {
+        Command::new(env!("CARGO_BIN_EXE_rlox"))
+            .args(args)
+            .arg(&script)
+            .output()
+            .unwrap()
+    };
+
+    let denied = run(&[]);
+    assert!(!denied.status.success());
+    assert!(String::from_utf8(denied.stderr)
+        .unwrap()
+        .contains("is not allowed"));
+
+    let allowed = run(&[format!("--allow-read={}", dir.to_str().unwrap())]);
+    assert!(allowed.status.success());
+    assert_eq!(String::from_utf8(allowed.stdout).unwrap(), "hunter2\n");
+}
@@ -0,0 +1,35 @@
+//! Helpers shared by the integration tests. Each test crate uses only some
+//! of them.
+#![allow(dead_code)]
+
+use std::{env, path::PathBuf, process};
+
+use rlox::{errors::Error, interpreter::LoxValue, lox::Lox};
+
+/// The value of `source` run in a fresh `Lox`, which must succeed.
+pub fn eval(source: &str) -> LoxValue {
+    Lox::new().eval(source).unwrap()
+}
+
+/// What `print` would show for the value of `source`.
+pub fn show(source: &str) -> String {
+    eval(source).stringify()
+}
+
+/// The message of the runtime error `source` fails with in a fresh `Lox`.
+pub fn error(source: &str) -> String {
+    error_in(&Lox::new(), source)
+}
+
+/// The message of the runtime error `source` fails with in `lox`.
+pub fn error_in(lox: &Lox, source: &str) -> String {
+    match lox.eval(source) {
+        Err(Error::RuntimeError(_, message)) => message,
+        other => panic!("expected a runtime error, got {:?}", other),
+    }
+}
+
+/// A path in the temp directory that no other test process will use.
+pub fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("rlox-{}-{}", process::id(), name))
+}
@@ -0,0 +1,17 @@
+use std::process::Command;
+
+/// Runs the bundled `.lox` tests in `tests/lox` through the conformance runner.
+#[test]
+fn bundled_lox_tests_pass() {
+    let output = Command::new(env!("CARGO_BIN_EXE_conformance"))
+        .arg(format!("--interpreter={}", env!("CARGO_BIN_EXE_rlox")))
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/lox"))
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
@@ -0,0 +1,64 @@
+use rlox::{errors::Error, interpreter::LoxValue, lox::Lox};
+
+#[test]
+fn eval_returns_the_last_expression() {
+    let lox = Lox::new();
+    assert_eq!(lox.eval("1 + 2;").unwrap(), LoxValue::from(3.0));
+    assert_eq!(
+        lox.eval("var a = \"b\"; a + \"c\";").unwrap(),
+        LoxValue::from("bc")
+    );
+}
+
+#[test]
+fn eval_returns_nil_without_a_trailing_expression() {
+    let lox = Lox::new();
+    assert!(lox.eval("var a = 1;").unwrap().is_nil());
+    assert!(lox.eval("").unwrap().is_nil());
+}
+
+#[test]
+fn eval_reports_errors() {
+    let lox = Lox::new();
+    assert!(matches!(lox.eval("1 +;"), Err(Error::ParseError(1, _))));
+    assert!(matches!(lox.eval("-nil;"), Err(Error::RuntimeError(1, _))));
+}
+
+#[test]
+fn globals_are_shared_with_scripts() {
+    let lox = Lox::new();
+    lox.set_global("name", "lox");
+    lox.set_global("missing", None::<f64>);
+    lox.eval("var greeting = \"hello \" + name;").unwrap();
+
+    assert_eq!(
+        lox.get_global("greeting").unwrap(),
+        LoxValue::from("hello lox")
+    );
+    assert!(lox.get_global("missing").unwrap().is_nil());
+    assert!(lox.get_global("undefined").is_none());
+}
+
+#[test]
+fn values_convert_to_rust_types() {
+    assert_eq!(f64::try_from(LoxValue::from(1.5)).unwrap(), 1.5);
+    assert!(bool::try_from(LoxValue::from(true)).unwrap());
+    assert_eq!(String::try_from(LoxValue::from("s")).unwrap(), "s");
+    assert_eq!(Option::<f64>::try_from(LoxValue::nil()).unwrap(), None);
+    assert_eq!(
+        Option::<String>::try_from(LoxValue::from("s")).unwrap(),
+        Some("s".to_string())
+    );
+}
+
+#[test]
+fn conversions_to_the_wrong_type_fail() {
+    assert!(matches!(
+        f64::try_from(LoxValue::from("1")),
+        Err(Error::ConversionError(_))
+    ));
+    assert!(matches!(
+        Option::<bool>::try_from(LoxValue::from(0.0)),
+        Err(Error::ConversionError(_))
+    ));
+}
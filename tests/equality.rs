@@ -0,0 +1,72 @@
+use rlox::scanner::token::LiteralType::{self, *};
+
+fn samples() -> Vec<LiteralType> {
+    vec![
+        LString("a".to_string()),
+        LString("b".to_string()),
+        LString("".to_string()),
+        LString("1".to_string()),
+        LNumber(1.0),
+        LNumber(2.0),
+        LNumber(0.0),
+        LBoolean(true),
+        LBoolean(false),
+        LNil,
+    ]
+}
+
+#[test]
+fn equality_matrix() {
+    let samples = samples();
+    for (i, left) in samples.iter().enumerate() {
+        for (j, right) in samples.iter().enumerate() {
+            let expected = i == j;
+            assert_eq!(
+                left.clone().equal(right.clone()),
+                LBoolean(expected),
+                "{:?} == {:?}",
+                left,
+                right
+            );
+            assert_eq!(
+                left.clone().not_equal(right.clone()),
+                LBoolean(!expected),
+                "{:?} != {:?}",
+                left,
+                right
+            );
+        }
+    }
+}
+
+#[test]
+fn equal_values_of_the_same_type() {
+    assert_eq!(
+        LString("a".to_string()).equal(LString("a".to_string())),
+        LBoolean(true)
+    );
+    assert_eq!(LNumber(1.5).equal(LNumber(1.5)), LBoolean(true));
+    assert_eq!(LNumber(0.0).equal(LNumber(-0.0)), LBoolean(true));
+    assert_eq!(LBoolean(false).equal(LBoolean(false)), LBoolean(true));
+    assert_eq!(LNil.equal(LNil), LBoolean(true));
+}
+
+#[test]
+fn nan_is_not_equal_to_itself() {
+    assert_eq!(LNumber(f64::NAN).equal(LNumber(f64::NAN)), LBoolean(false));
+    assert_eq!(
+        LNumber(f64::NAN).not_equal(LNumber(f64::NAN)),
+        LBoolean(true)
+    );
+}
+
+#[test]
+fn equality_through_the_binary_operator() {
+    use rlox::scanner::tokenType::TokenType::{BangEqual, EqualEqual};
+
+    assert_eq!(LNil.apply(EqualEqual, LBoolean(false)), Ok(LBoolean(false)));
+    assert_eq!(
+        LString("1".to_string()).apply(BangEqual, LNumber(1.0)),
+        Ok(LBoolean(true))
+    );
+}
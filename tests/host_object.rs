@@ -0,0 +1,131 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use rlox::{
+    errors::Error, host::HostObject, interpreter::LoxValue, lox::Lox, native::NativeResult,
+};
+
+/// A settings store scripts can read, change and query.
+#[derive(Default)]
+struct Config {
+    values: RefCell<HashMap<String, LoxValue>>,
+}
+
+impl HostObject for Config {
+    fn type_name(&self) -> &str {
+        "Config"
+    }
+
+    fn get(&self, name: &str) -> Option<LoxValue> {
+        self.values.borrow().get(name).cloned()
+    }
+
+    fn set(&self, name: &str, value: LoxValue) -> Result<(), String> {
+        self.values.borrow_mut().insert(name.to_string(), value);
+        Ok(())
+    }
+
+    fn call_method(&self, name: &str, arguments: &[LoxValue]) -> NativeResult {
+        match (name, arguments) {
+            ("has", [key]) => {
+                let key = String::try_from(key.clone()).map_err(|_| "Key must be a string.")?;
+                Ok(LoxValue::from(self.values.borrow().contains_key(&key)))
+            }
+            ("has", _) => Err("has() takes one argument.".into()),
+            _ => Err(format!("Undefined method '{}'.", name).into()),
+        }
+    }
+}
+
+/// An object that only has the default hooks.
+struct Opaque;
+
+impl HostObject for Opaque {
+    fn type_name(&self) -> &str {
+        "Opaque"
+    }
+}
+
+fn lox_with_config() -> Lox {
+    let lox = Lox::new();
+    let config = Config::default();
+    config.set("port", LoxValue::from(8080.0)).unwrap();
+    lox.set_global("config", LoxValue::host_object(config));
+    lox
+}
+
+#[test]
+fn scripts_read_and_write_properties() {
+    let lox = lox_with_config();
+    assert_eq!(
+        lox.eval("config.port + 1;").unwrap(),
+        LoxValue::from(8081.0)
+    );
+    assert_eq!(
+        lox.eval("config.host = \"localhost\"; config.host;")
+            .unwrap(),
+        LoxValue::from("localhost")
+    );
+}
+
+#[test]
+fn scripts_call_methods() {
+    let lox = lox_with_config();
+    assert_eq!(
+        lox.eval("config.has(\"port\");").unwrap(),
+        LoxValue::from(true)
+    );
+    assert_eq!(
+        lox.eval("config.has(\"user\");").unwrap(),
+        LoxValue::from(false)
+    );
+    assert!(matches!(
+        lox.eval("config.reload();"),
+        Err(Error::RuntimeError(1, message)) if message == "Undefined method 'reload'."
+    ));
+}
+
+#[test]
+fn missing_properties_are_runtime_errors() {
+    let lox = lox_with_config();
+    assert!(matches!(
+        lox.eval("config.user;"),
+        Err(Error::RuntimeError(1, message)) if message == "Undefined property 'user'."
+    ));
+}
+
+#[test]
+fn default_hooks_reject_everything() {
+    let lox = Lox::new();
+    lox.set_global("opaque", LoxValue::host_object(Opaque));
+    assert!(lox.eval("opaque.a;").is_err());
+    assert!(lox.eval("opaque.a = 1;").is_err());
+    assert!(lox.eval("opaque.a();").is_err());
+    assert_eq!(
+        lox.get_global("opaque").unwrap().stringify(),
+        "Opaque instance"
+    );
+}
+
+#[test]
+fn only_objects_have_properties() {
+    let lox = Lox::new();
+    assert!(matches!(
+        lox.eval("var a = 1; a.b;"),
+        Err(Error::RuntimeError(1, message)) if message == "Only instances have properties."
+    ));
+    assert!(matches!(
+        lox.eval("var a = 1; a.b = 2;"),
+        Err(Error::RuntimeError(1, message)) if message == "Only instances have fields."
+    ));
+}
+
+#[test]
+fn objects_compare_by_identity() {
+    let lox = lox_with_config();
+    lox.set_global("other", LoxValue::host_object(Config::default()));
+    assert_eq!(
+        lox.eval("var same = config; same == config;").unwrap(),
+        LoxValue::from(true)
+    );
+    assert_eq!(lox.eval("config == other;").unwrap(), LoxValue::from(false));
+}
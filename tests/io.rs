@@ -0,0 +1,49 @@
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+    rc::Rc,
+};
+
+use rlox::{interpreter::Interpreter, parser::Parser, scanner::Scanner};
+
+/// A writer whose contents the test can still read after handing it over.
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn print_writes_to_the_given_output() {
+    let output = SharedBuffer::default();
+    let interpreter = Interpreter::with_io(Box::new(output.clone()), Box::new(io::empty()));
+
+    let source = "var a = 1;\nprint a + 2;\nprint \"done\";".to_string();
+    let statements = Parser::new(Scanner::new(source)).parse().ok().unwrap();
+    interpreter.interpret(&statements).ok().unwrap();
+
+    assert_eq!(String::from_utf8(output.0.take()).unwrap(), "3\ndone\n");
+}
+
+#[test]
+fn read_line_reads_from_the_given_input() {
+    let input = io::Cursor::new("first\r\nsecond\n");
+    let interpreter = Interpreter::with_io(Box::new(io::sink()), Box::new(input));
+
+    assert_eq!(
+        interpreter.read_line().ok().unwrap().as_deref(),
+        Some("first")
+    );
+    assert_eq!(
+        interpreter.read_line().ok().unwrap().as_deref(),
+        Some("second")
+    );
+    assert_eq!(interpreter.read_line().ok().unwrap(), None);
+}
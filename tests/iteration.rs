@@ -0,0 +1,199 @@
+use std::cell::Cell;
+
+use rlox::{
+    errors::Error, host::HostObject, interpreter::LoxValue, lox::Lox, native::NativeResult,
+};
+
+mod common;
+
+use common::error_in;
+
+/// Counts down from `from` to 1, through the `iter()`/`next()` protocol.
+struct Countdown {
+    from: f64,
+}
+
+impl HostObject for Countdown {
+    fn type_name(&self) -> &str {
+        "Countdown"
+    }
+
+    fn call_method(&self, name: &str, _arguments: &[LoxValue]) -> NativeResult {
+        match name {
+            "iter" => Ok(LoxValue::host_object(CountdownIterator {
+                next: Cell::new(self.from),
+            })),
+            _ => Err(format!("Undefined method '{}'.", name).into()),
+        }
+    }
+}
+
+struct CountdownIterator {
+    next: Cell<f64>,
+}
+
+impl HostObject for CountdownIterator {
+    fn type_name(&self) -> &str {
+        "CountdownIterator"
+    }
+
+    fn call_method(&self, name: &str, _arguments: &[LoxValue]) -> NativeResult {
+        match name {
+            "next" => {
+                let next = self.next.get();
+                if next < 1.0 {
+                    return Ok(LoxValue::nil());
+                }
+                self.next.set(next - 1.0);
+                Ok(LoxValue::from(next))
+            }
+            _ => Err(format!("Undefined method '{}'.", name).into()),
+        }
+    }
+}
+
+/// Never ends.
+struct Forever;
+
+impl HostObject for Forever {
+    fn type_name(&self) -> &str {
+        "Forever"
+    }
+
+    fn call_method(&self, _name: &str, _arguments: &[LoxValue]) -> NativeResult {
+        Ok(LoxValue::host_object(Forever))
+    }
+}
+
+#[test]
+fn loops_over_each_kind_of_iterable() {
+    let lox = Lox::new();
+    lox.eval("var seen = [];").unwrap();
+    lox.eval("for (x in [1, 2]) seen.push(x);").unwrap();
+    lox.eval("for (k in {\"a\": 1, \"b\": 2}) seen.push(k);")
+        .unwrap();
+    lox.eval("for (c in \"hé\") seen.push(c);").unwrap();
+    lox.eval("for (i in range(3, 5)) seen.push(i);").unwrap();
+    assert_eq!(
+        lox.eval("seen;").unwrap().stringify(),
+        "[1, 2, a, b, h, é, 3, 4]"
+    );
+}
+
+#[test]
+fn host_objects_iterate_through_iter_and_next() {
+    let lox = Lox::new();
+    lox.set_global("countdown", LoxValue::host_object(Countdown { from: 3.0 }));
+    lox.eval("var seen = []; for (n in countdown) seen.push(n);")
+        .unwrap();
+    assert_eq!(lox.eval("seen;").unwrap().stringify(), "[3, 2, 1]");
+}
+
+#[test]
+fn loops_nest() {
+    let lox = Lox::new();
+    lox.eval("var pairs = [];").unwrap();
+    lox.eval("for (a in range(0, 2)) for (b in \"xy\") pairs.push(str(a) + b);")
+        .unwrap();
+    assert_eq!(lox.eval("pairs;").unwrap().stringify(), "[0x, 0y, 1x, 1y]");
+}
+
+#[test]
+fn the_body_may_change_the_list() {
+    let lox = Lox::new();
+    lox.eval("var xs = [1, 2, 3, 4]; var seen = [];").unwrap();
+    lox.eval("for (x in xs) { seen.push(x); xs.pop(); }")
+        .unwrap();
+    assert_eq!(lox.eval("seen;").unwrap().stringify(), "[1, 2]");
+}
+
+#[test]
+fn the_loop_variable_is_scoped_to_the_loop() {
+    let lox = Lox::new();
+    lox.eval("for (x in [1]) { var inner = x; }").unwrap();
+    assert_eq!(error_in(&lox, "x;"), "Undefined variable 'x'.");
+    assert_eq!(error_in(&lox, "inner;"), "Undefined variable 'inner'.");
+}
+
+#[test]
+fn errors_leave_the_scope() {
+    let lox = Lox::new();
+    assert_eq!(
+        error_in(&lox, "for (x in [1]) { var local = 1; nil[0]; }"),
+        "Only lists, maps and strings can be indexed."
+    );
+    assert_eq!(error_in(&lox, "local;"), "Undefined variable 'local'.");
+    lox.eval("var after = 1;").unwrap();
+    assert!(lox.interpreter().globals().get("after").is_some());
+}
+
+#[test]
+fn fuel_stops_endless_loops() {
+    let lox = Lox::new();
+    lox.set_global("forever", LoxValue::host_object(Forever));
+    lox.interpreter().set_fuel(Some(1_000));
+    assert!(matches!(
+        lox.eval("for (x in forever) {}"),
+        Err(Error::OutOfFuel)
+    ));
+}
+
+#[test]
+fn misuse_is_a_runtime_error() {
+    let lox = Lox::new();
+    assert_eq!(
+        error_in(&lox, "for (x in 1) print x;"),
+        "Can only iterate over lists, maps, strings and iterable objects."
+    );
+    assert_eq!(
+        error_in(&lox, "for (x in math) print x;"),
+        "Undefined property 'iter'."
+    );
+    assert_eq!(
+        error_in(&lox, "range(0, \"3\");"),
+        "range() expects numbers but got string."
+    );
+    assert_eq!(
+        error_in(&lox, "range(0, 1).iter(1);"),
+        "Expected 0 arguments but got 1."
+    );
+}
+
+#[test]
+fn ranges_stop_at_the_edge_of_exact_integers() {
+    let lox = Lox::new();
+    lox.eval("var seen = [];").unwrap();
+    lox.eval("for (i in range(9007199254740990, 9007199254740992)) seen.push(i);")
+        .unwrap();
+    assert_eq!(
+        lox.eval("seen;").unwrap().stringify(),
+        "[9007199254740990, 9007199254740991]"
+    );
+    assert_eq!(
+        error_in(&lox, "range(9007199254740992, 9007199254740994);"),
+        "range() expects bounds between -2^53 and 2^53 but got 9007199254740994."
+    );
+    assert_eq!(
+        error_in(&lox, "range(-1 / 0, 0);"),
+        "range() expects bounds between -2^53 and 2^53 but got -inf."
+    );
+    assert_eq!(
+        error_in(&lox, "range(0, math.nan);"),
+        "range() expects bounds between -2^53 and 2^53 but got NaN."
+    );
+}
+
+#[test]
+fn parse_errors() {
+    for source in [
+        "for (x of [1]) print x;",
+        "for x in [1] print x;",
+        "{ print 1;",
+    ] {
+        assert!(
+            matches!(Lox::new().eval(source), Err(Error::ParseError(..))),
+            "{}",
+            source
+        );
+    }
+}
@@ -0,0 +1,328 @@
+use std::{
+    mem,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
+
+use rlox::{
+    errors::Error, interpreter::LoxValue, lox::Lox, map::MapKey, parser::Parser, scanner::Scanner,
+};
+
+/// Parenthesized groups, blocks and a chain of additions, each `n` deep.
+fn nested_scripts(n: usize) -> [String; 3] {
+    [
+        format!("print {}1{};", "(".repeat(n), ")".repeat(n)),
+        format!("{}{}", "{".repeat(n), "}".repeat(n)),
+        format!("print 1{};", " + 1".repeat(n)),
+    ]
+}
+
+fn item_size() -> usize {
+    mem::size_of::<LoxValue>()
+}
+
+fn entry_size() -> usize {
+    mem::size_of::<MapKey>() + mem::size_of::<LoxValue>()
+}
+
+/// A script that increments `count` `n` times.
+fn counting_script(n: usize) -> String {
+    "var count = 0;\n".to_string() + &"count = count + 1;\n".repeat(n)
+}
+
+fn count(lox: &Lox) -> f64 {
+    f64::try_from(lox.get_global("count").unwrap()).unwrap()
+}
+
+#[test]
+fn scripts_within_budget_run_normally() {
+    let lox = Lox::new();
+    lox.interpreter().set_fuel(Some(1_000));
+
+    assert_eq!(lox.eval("1 + 2 * 3;").unwrap(), LoxValue::from(7.0));
+    assert!(lox.interpreter().fuel().unwrap() < 1_000);
+}
+
+#[test]
+fn running_out_of_fuel_stops_the_script() {
+    let lox = Lox::new();
+    lox.interpreter().set_fuel(Some(100));
+
+    assert!(matches!(
+        lox.eval(&counting_script(200)),
+        Err(Error::OutOfFuel)
+    ));
+    assert_eq!(lox.interpreter().fuel(), Some(0));
+    assert!(count(&lox) > 0.0 && count(&lox) < 100.0);
+}
+
+#[test]
+fn fuel_can_be_topped_up() {
+    let lox = Lox::new();
+    lox.interpreter().set_fuel(Some(0));
+    assert!(matches!(lox.eval("1;"), Err(Error::OutOfFuel)));
+
+    lox.interpreter().set_fuel(None);
+    assert_eq!(lox.eval("1;").unwrap(), LoxValue::from(1.0));
+}
+
+#[test]
+fn a_set_flag_cancels_before_running() {
+    let lox = Lox::new();
+    let cancel = Arc::new(AtomicBool::new(true));
+    lox.interpreter().set_cancel_flag(cancel);
+
+    assert!(matches!(
+        lox.eval(&counting_script(10)),
+        Err(Error::Cancelled)
+    ));
+    assert_eq!(lox.get_global("count"), None);
+}
+
+#[test]
+fn setting_the_flag_stops_a_running_script() {
+    let lox = Lox::new();
+    let cancel = Arc::new(AtomicBool::new(false));
+    lox.interpreter().set_cancel_flag(cancel.clone());
+    lox.define_native("stop", 0, move |_| {
+        cancel.store(true, Ordering::Relaxed);
+        Ok(LoxValue::nil())
+    });
+
+    let script = "stop();\n".to_string() + &counting_script(500);
+    assert!(matches!(lox.eval(&script), Err(Error::Cancelled)));
+    assert!(count(&lox) < 500.0);
+}
+
+#[test]
+fn another_thread_can_cancel() {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let (started, running) = mpsc::channel();
+    let watchdog = {
+        let cancel = cancel.clone();
+        thread::spawn(move || {
+            running.recv().unwrap();
+            cancel.store(true, Ordering::Relaxed);
+        })
+    };
+
+    let lox = Lox::new();
+    lox.interpreter().set_cancel_flag(cancel);
+    lox.define_native("started", 0, move |_| {
+        started.send(()).unwrap();
+        Ok(LoxValue::nil())
+    });
+
+    // Without the watchdog this loop would run for days.
+    let script = "started(); for (i in range(0, 1000000000000)) {}";
+    assert!(matches!(lox.eval(script), Err(Error::Cancelled)));
+    watchdog.join().unwrap();
+}
+
+#[test]
+fn nested_calls_past_the_limit_overflow() {
+    let lox = Rc::new(Lox::new());
+    lox.interpreter().set_max_call_depth(Some(20));
+
+    // Calls back into the interpreter, so every call nests one deeper.
+    let weak = Rc::downgrade(&lox);
+    lox.define_native("recurse", 0, move |_| {
+        let lox = weak.upgrade().unwrap();
+        Ok(lox.eval("recurse();")?)
+    });
+
+    let error = lox.eval("recurse();").unwrap_err();
+    assert_eq!(error.trace().len(), 20);
+    assert!(matches!(
+        error.cause(),
+        Error::RuntimeError(1, message) if message == "Stack overflow."
+    ));
+
+    // The depth unwinds with the error, so later calls work again.
+    lox.define_native("one", 0, |_| Ok(LoxValue::from(1.0)));
+    assert_eq!(lox.eval("one();").unwrap(), LoxValue::from(1.0));
+}
+
+#[test]
+fn nesting_past_the_limit_is_a_parse_error() {
+    let lox = Lox::new();
+    let [groups, blocks, _] = nested_scripts(20_000);
+    let [.., sum] = nested_scripts(100_000);
+    for script in [groups, blocks, sum] {
+        assert!(matches!(
+            lox.eval(&script),
+            Err(Error::ParseError(1, message)) if message == "Nesting is too deep."
+        ));
+    }
+
+    lox.interpreter().set_max_nesting_depth(Some(40));
+    for script in nested_scripts(30) {
+        lox.eval(&script).unwrap();
+    }
+    for script in nested_scripts(50) {
+        assert!(matches!(lox.eval(&script), Err(Error::ParseError(..))));
+    }
+}
+
+#[test]
+fn nesting_past_the_limit_overflows_at_runtime() {
+    let lox = Lox::new();
+    lox.interpreter().set_max_nesting_depth(Some(40));
+    for script in nested_scripts(50) {
+        // Parsed without a limit, as a tree built by the host might be.
+        let statements = Parser::new(Scanner::new(script))
+            .with_max_depth(None)
+            .parse()
+            .unwrap();
+        let error = lox.interpreter().interpret(&statements).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::RuntimeError(_, message) if message == "Stack overflow."
+        ));
+    }
+
+    // The depth unwinds with the error, so later scripts run again.
+    assert_eq!(lox.eval("(((1)));").unwrap(), LoxValue::from(1.0));
+}
+
+#[test]
+fn string_allocations_count_against_the_allocation_limit() {
+    let lox = Lox::new();
+    lox.interpreter().set_allocation_limit(Some(64));
+
+    lox.eval("var s = \"0123456789\"; s = s + s;").unwrap();
+    assert_eq!(lox.interpreter().allocated(), 20);
+
+    assert!(matches!(
+        lox.eval("s = s + s; s = s + s;"),
+        Err(Error::OutOfMemory)
+    ));
+    assert_eq!(
+        lox.eval("s;").unwrap(),
+        LoxValue::from("0123456789".repeat(4))
+    );
+}
+
+#[test]
+fn values_built_by_natives_are_charged() {
+    let lox = Lox::new();
+    lox.interpreter().set_allocation_limit(Some(1_000));
+    lox.eval("var s = \"ab\".repeat(100);").unwrap();
+    assert_eq!(lox.interpreter().allocated(), 200);
+
+    let charged = |source: &str| {
+        let before = lox.interpreter().allocated();
+        lox.eval(source).unwrap();
+        lox.interpreter().allocated() - before
+    };
+    assert_eq!(charged("str(12);"), 2);
+    assert_eq!(charged("s.upper();"), 200);
+    assert_eq!(charged("s.substring(0, 10);"), 10);
+    assert_eq!(charged("({1: 2}).keys();"), item_size() + entry_size());
+
+    assert!(matches!(lox.eval("s + s + s;"), Err(Error::OutOfMemory)));
+}
+
+#[test]
+fn values_passed_along_are_not_charged_again() {
+    let lox = Lox::new();
+    lox.interpreter()
+        .set_allocation_limit(Some(1_000 + 100 * item_size()));
+    lox.eval("var s = \"x\".repeat(1000); var xs = [s]; var m = {1: s};")
+        .unwrap();
+    lox.define_native("first", 2, |args| Ok(args[0].clone()));
+    let allocated = lox.interpreter().allocated();
+
+    lox.eval("for (i in range(0, 50)) xs.reduce(first, s);")
+        .unwrap();
+    lox.eval("for (i in range(0, 50)) first(s, s);").unwrap();
+    lox.eval("m.remove(1);").unwrap();
+    assert_eq!(lox.interpreter().allocated(), allocated);
+
+    // Each push charges for its slot in the list, but not for the string.
+    lox.eval("for (i in range(0, 50)) xs.push(xs.pop());")
+        .unwrap();
+    assert_eq!(lox.interpreter().allocated(), allocated + 50 * item_size());
+}
+
+#[test]
+fn repeat_checks_the_limit_before_building_its_string() {
+    let lox = Lox::new();
+    lox.interpreter().set_allocation_limit(Some(1_000));
+
+    assert!(matches!(
+        lox.eval("\"ab\".repeat(1000);"),
+        Err(Error::OutOfMemory)
+    ));
+    assert_eq!(lox.interpreter().allocated(), 0);
+    assert_eq!(
+        lox.eval("\"ab\".repeat(500);").unwrap(),
+        LoxValue::from("ab".repeat(500))
+    );
+}
+
+#[test]
+fn growing_a_list_is_charged_per_item() {
+    let lox = Lox::new();
+    let item = mem::size_of::<LoxValue>();
+    lox.interpreter().set_allocation_limit(Some(item * 10));
+
+    lox.eval("var xs = [1, 2];").unwrap();
+    assert_eq!(lox.interpreter().allocated(), item * 2);
+    lox.eval("xs.push(3); xs.insert(0, 0);").unwrap();
+    assert_eq!(lox.interpreter().allocated(), item * 4);
+
+    let script = "for (i in range(0, 100)) xs.push(i);";
+    assert!(matches!(lox.eval(script), Err(Error::OutOfMemory)));
+    assert_eq!(f64::try_from(lox.eval("len(xs);").unwrap()).unwrap(), 10.0);
+}
+
+#[test]
+fn lists_built_by_methods_are_charged() {
+    let lox = Lox::new();
+    let item = mem::size_of::<LoxValue>();
+    lox.interpreter().set_allocation_limit(Some(item * 100));
+    lox.eval("var xs = [1, 2, 3, 4];").unwrap();
+
+    let charged = |source: &str| {
+        let before = lox.interpreter().allocated();
+        lox.eval(source).unwrap();
+        lox.interpreter().allocated() - before
+    };
+    assert_eq!(charged("xs.slice(1, 3);"), item * 2);
+    assert_eq!(charged("xs.map(math.abs);"), item * 4);
+    assert_eq!(charged("xs.filter(math.abs);"), item * 4);
+}
+
+#[test]
+fn new_map_entries_are_charged() {
+    let lox = Lox::new();
+    let entry = mem::size_of::<MapKey>() + mem::size_of::<LoxValue>();
+    lox.interpreter().set_allocation_limit(Some(entry * 10));
+
+    lox.eval("var m = {1: 1};").unwrap();
+    assert_eq!(lox.interpreter().allocated(), entry);
+    lox.eval("m[2] = 2; m[1] = 3; m[2] = 4;").unwrap();
+    assert_eq!(lox.interpreter().allocated(), entry * 2);
+
+    let script = "for (i in range(0, 100)) m[i] = i;";
+    assert!(matches!(lox.eval(script), Err(Error::OutOfMemory)));
+    assert_eq!(f64::try_from(lox.eval("m.len();").unwrap()).unwrap(), 10.0);
+}
+
+#[test]
+fn freed_values_still_count_against_the_allocation_limit() {
+    let lox = Lox::new();
+    lox.interpreter().set_allocation_limit(Some(1_000));
+
+    lox.eval("var s; s = \"ab\" + \"cd\"; s = \"ab\" + \"cd\";")
+        .unwrap();
+    assert_eq!(lox.interpreter().allocated(), 8);
+
+    let script = "for (i in range(0, 1000)) { s = \"ab\" + \"cd\"; }";
+    assert!(matches!(lox.eval(script), Err(Error::OutOfMemory)));
+}
@@ -0,0 +1,131 @@
+use rlox::{errors::Error, interpreter::LoxValue, lox::Lox};
+
+mod common;
+
+use common::{error, eval, show};
+
+#[test]
+fn literals_and_indexing() {
+    assert_eq!(show("[1, \"two\", nil, [true]];"), "[1, two, nil, [true]]");
+    assert_eq!(show("[];"), "[]");
+    assert_eq!(eval("[10, 20, 30][1];"), LoxValue::from(20.0));
+    assert_eq!(eval("[[1, 2], [3, 4]][1][0];"), LoxValue::from(3.0));
+    assert_eq!(eval("\"héllo\"[1];"), LoxValue::from("é"));
+    assert_eq!(eval("type([1]);"), LoxValue::from("list"));
+    assert_eq!(eval("len([1, 2, 3]);"), LoxValue::from(3.0));
+}
+
+#[test]
+fn index_assignment_is_shared() {
+    assert_eq!(
+        show("var xs = [1, 2, 3]; var ys = xs; ys[0] = \"one\"; xs;"),
+        "[one, 2, 3]"
+    );
+    assert_eq!(eval("var xs = [1]; xs[0] = 5;"), LoxValue::from(5.0));
+}
+
+#[test]
+fn adding_and_removing() {
+    assert_eq!(
+        show("var xs = [1]; xs.push(2); xs.insert(0, 0); xs.insert(3, 3); xs;"),
+        "[0, 1, 2, 3]"
+    );
+    assert_eq!(eval("[1, 2].push(3);"), LoxValue::from(3.0));
+    assert_eq!(
+        show("var xs = [1, 2, 3]; var last = xs.pop(); [last, xs.remove(0), xs];"),
+        "[3, 1, [2]]"
+    );
+    assert_eq!(eval("[1, 2, 3].len();"), LoxValue::from(3.0));
+    assert_eq!(show("[1, 2, 3, 4].slice(1, 3);"), "[2, 3]");
+}
+
+#[test]
+fn higher_order_methods() {
+    assert_eq!(show("[1, 4, 9].map(math.sqrt);"), "[1, 2, 3]");
+    assert_eq!(
+        show("[1, nil, 0, false, \"\"].filter(str);"),
+        "[1, nil, 0, false, ]"
+    );
+    assert_eq!(show("[\"a\", \"\"].filter(\"ab\".startsWith);"), "[a, ]");
+    assert_eq!(eval("[3, 1, 2].reduce(math.max, 0);"), LoxValue::from(3.0));
+    assert_eq!(eval("[].reduce(math.max, 7);"), LoxValue::from(7.0));
+}
+
+#[test]
+fn sorting() {
+    assert_eq!(
+        show("var xs = [3, -1, 2.5]; xs.sort(); xs;"),
+        "[-1, 2.5, 3]"
+    );
+    assert_eq!(
+        show("var xs = [\"pear\", \"apple\"]; xs.sort(); xs;"),
+        "[apple, pear]"
+    );
+}
+
+#[test]
+fn callbacks_may_change_the_list() {
+    assert_eq!(
+        show("var xs = [1, 2]; xs.map(xs.push); xs;"),
+        "[1, 2, 1, 2]"
+    );
+}
+
+#[test]
+fn misuse_is_a_runtime_error() {
+    assert_eq!(error("[1, 2][2];"), "Index out of bounds.");
+    assert_eq!(
+        error("[1, 2][0.5];"),
+        "Index must be a non-negative integer."
+    );
+    assert_eq!(
+        error("[1, 2][-1];"),
+        "Index must be a non-negative integer."
+    );
+    assert_eq!(
+        error("nil[0];"),
+        "Only lists, maps and strings can be indexed."
+    );
+    assert_eq!(
+        error("\"abc\"[0] = \"x\";"),
+        "Only list and map items can be assigned."
+    );
+    assert_eq!(error("var xs = []; xs[0] = 1;"), "Index out of bounds.");
+    assert_eq!(error("[].pop();"), "Can't pop from an empty list.");
+    assert_eq!(error("[1].insert(2, 0);"), "Index out of bounds.");
+    assert_eq!(error("[1].slice(1, 0);"), "Slice range out of bounds.");
+    assert_eq!(
+        error("[1].map(1);"),
+        "map() expects a function but got number."
+    );
+    assert_eq!(
+        error("[1].map(math.pow);"),
+        "Expected 2 arguments but got 1."
+    );
+    assert_eq!(
+        error("[1, \"a\"].sort();"),
+        "sort() expects a list of only numbers or only strings."
+    );
+    assert_eq!(error("[1].first();"), "Undefined property 'first'.");
+}
+
+#[test]
+fn lists_that_contain_themselves_print() {
+    assert_eq!(show("var xs = [1]; xs.push(xs); xs;"), "[1, [...]]");
+    assert_eq!(show("var xs = []; xs.push(xs); str(xs);"), "[[...]]");
+    assert_eq!(show("var a = []; var b = [a]; a.push(b); a;"), "[[[...]]]");
+    // Only a list inside itself is cut short, not one that merely repeats.
+    assert_eq!(show("var a = [1]; [a, a];"), "[[1], [1]]");
+}
+
+#[test]
+fn parse_errors() {
+    assert!(matches!(
+        Lox::new().eval("[1, 2;"),
+        Err(Error::ParseError(..))
+    ));
+    assert!(matches!(
+        Lox::new().eval("[1][0;"),
+        Err(Error::ParseError(..))
+    ));
+}
@@ -0,0 +1,105 @@
+use rlox::{errors::Error, interpreter::LoxValue, lox::Lox};
+
+mod common;
+
+use common::{error, eval, show};
+
+#[test]
+fn literals_and_lookups() {
+    assert_eq!(show("({\"a\": 1, \"b\": [2]});"), "{\"a\": 1, \"b\": [2]}");
+    assert_eq!(show("({});"), "{}");
+    assert_eq!(eval("({\"a\": 1, \"b\": 2})[\"b\"];"), LoxValue::from(2.0));
+    assert_eq!(eval("type({});"), LoxValue::from("map"));
+    assert_eq!(eval("len({1: 1, 2: 2});"), LoxValue::from(2.0));
+}
+
+#[test]
+fn keys_can_be_any_hashable_value() {
+    assert_eq!(
+        show("var m = {1: \"one\", true: \"yes\", nil: \"none\"}; [m[1], m[true], m[nil]];"),
+        "[one, yes, none]"
+    );
+    assert_eq!(eval("({0: \"zero\"})[-0];"), LoxValue::from("zero"));
+    assert_eq!(eval("({1: \"number\"}).has(\"1\");"), LoxValue::from(false));
+}
+
+#[test]
+fn entries_keep_insertion_order() {
+    assert_eq!(
+        show("var m = {\"b\": 1, \"a\": 2}; m[\"c\"] = 3; m[\"b\"] = 4; m;"),
+        "{\"b\": 4, \"a\": 2, \"c\": 3}"
+    );
+    assert_eq!(show("({\"x\": 1, \"x\": 2});"), "{\"x\": 2}");
+}
+
+#[test]
+fn string_keys_are_quoted() {
+    assert_eq!(show("({\"1\": 1, 1: 2});"), "{\"1\": 1, 1: 2}");
+    assert_eq!(show("({true: 1, nil: 2});"), "{true: 1, nil: 2}");
+}
+
+#[test]
+fn maps_that_contain_themselves_print() {
+    assert_eq!(show("var m = {}; m[\"a\"] = m; m;"), "{\"a\": {...}}");
+    assert_eq!(show("var m = {}; m[1] = [m]; str(m);"), "{1: [{...}]}");
+    assert_eq!(
+        show("var m = {}; var xs = [m]; m[0] = xs; xs;"),
+        "[{0: [...]}]"
+    );
+    assert_eq!(show("var a = {}; ({1: a, 2: a});"), "{1: {}, 2: {}}");
+}
+
+#[test]
+fn assignment_is_shared() {
+    assert_eq!(
+        show("var m = {}; var n = m; n[\"k\"] = \"v\"; m;"),
+        "{\"k\": v}"
+    );
+    assert_eq!(eval("var m = {}; m[1] = 2;"), LoxValue::from(2.0));
+}
+
+#[test]
+fn methods() {
+    let lox = Lox::new();
+    lox.eval("var m = {\"a\": 1, \"b\": 2, \"c\": 3};").unwrap();
+    assert_eq!(lox.eval("m.keys();").unwrap().stringify(), "[a, b, c]");
+    assert_eq!(lox.eval("m.values();").unwrap().stringify(), "[1, 2, 3]");
+    assert_eq!(lox.eval("m.has(\"b\");").unwrap(), LoxValue::from(true));
+    assert_eq!(lox.eval("m.remove(\"b\");").unwrap(), LoxValue::from(2.0));
+    assert_eq!(lox.eval("m.has(\"b\");").unwrap(), LoxValue::from(false));
+    assert_eq!(lox.eval("m.len();").unwrap(), LoxValue::from(2.0));
+    assert_eq!(lox.eval("m[\"c\"];").unwrap(), LoxValue::from(3.0));
+    assert_eq!(lox.eval("m;").unwrap().stringify(), "{\"a\": 1, \"c\": 3}");
+}
+
+#[test]
+fn misuse_is_a_runtime_error() {
+    assert_eq!(error("({})[\"a\"];"), "Undefined key 'a'.");
+    assert_eq!(error("({}).remove(1);"), "Undefined key '1'.");
+    assert_eq!(
+        error("({[1]: 2});"),
+        "Map keys must be strings, numbers, booleans or nil, not list."
+    );
+    assert_eq!(
+        error("var m = {}; m[{}] = 1;"),
+        "Map keys must be strings, numbers, booleans or nil, not map."
+    );
+    assert_eq!(error("({})[math.nan];"), "NaN can't be a map key.");
+    assert_eq!(
+        error("({}).has([]);"),
+        "Map keys must be strings, numbers, booleans or nil, not list."
+    );
+    assert_eq!(error("({}).size();"), "Undefined property 'size'.");
+}
+
+#[test]
+fn parse_errors() {
+    assert!(matches!(
+        Lox::new().eval("({\"a\" 1});"),
+        Err(Error::ParseError(..))
+    ));
+    assert!(matches!(
+        Lox::new().eval("({\"a\": 1;"),
+        Err(Error::ParseError(..))
+    ));
+}
@@ -0,0 +1,81 @@
+use rlox::{interpreter::LoxValue, lox::Lox};
+
+mod common;
+
+use common::{error, eval};
+
+fn number(source: &str) -> f64 {
+    f64::try_from(eval(source)).unwrap()
+}
+
+#[test]
+fn rounding() {
+    assert_eq!(number("math.floor(-1.5);"), -2.0);
+    assert_eq!(number("math.ceil(1.2);"), 2.0);
+    assert_eq!(number("math.round(2.5);"), 3.0);
+    assert_eq!(number("math.round(-2.5);"), -3.0);
+    assert_eq!(number("math.abs(-4);"), 4.0);
+}
+
+#[test]
+fn powers_and_logarithms() {
+    assert_eq!(number("math.sqrt(16);"), 4.0);
+    assert_eq!(number("math.pow(2, 10);"), 1024.0);
+    assert_eq!(number("math.log(math.e);"), 1.0);
+    assert_eq!(number("math.log2(8);"), 3.0);
+    assert_eq!(number("math.log10(1000);"), 3.0);
+    assert_eq!(number("math.exp(0);"), 1.0);
+}
+
+#[test]
+fn trigonometry() {
+    assert_eq!(number("math.sin(0);"), 0.0);
+    assert_eq!(number("math.cos(0);"), 1.0);
+    assert!((number("math.atan2(1, 1) * 4;") - std::f64::consts::PI).abs() < 1e-12);
+    assert!((number("math.asin(1) * 2 - math.pi;")).abs() < 1e-12);
+}
+
+#[test]
+fn min_and_max() {
+    assert_eq!(number("math.min(3, -1);"), -1.0);
+    assert_eq!(number("math.max(3, -1);"), 3.0);
+}
+
+#[test]
+fn constants() {
+    assert_eq!(number("math.pi;"), std::f64::consts::PI);
+    assert_eq!(number("math.inf;"), f64::INFINITY);
+    assert!(number("math.nan;").is_nan());
+
+    let lox = Lox::new();
+    assert_eq!(
+        lox.eval("math.isNaN(math.nan);").unwrap(),
+        LoxValue::from(true)
+    );
+    assert_eq!(
+        lox.eval("math.isNaN(math.sqrt(-1));").unwrap(),
+        LoxValue::from(true)
+    );
+    assert_eq!(lox.eval("math.isNaN(1);").unwrap(), LoxValue::from(false));
+}
+
+#[test]
+fn functions_are_values() {
+    assert_eq!(number("var root = math.sqrt; root(81);"), 9.0);
+}
+
+#[test]
+fn misuse_is_a_runtime_error() {
+    assert_eq!(
+        error("math.floor(\"1\");"),
+        "math.floor() expects numbers but got string."
+    );
+    assert_eq!(
+        error("math.pow(2, nil);"),
+        "math.pow() expects numbers but got nil."
+    );
+    assert_eq!(error("math.max(1);"), "Expected 2 arguments but got 1.");
+    assert_eq!(error("math.cube(1);"), "Undefined property 'cube'.");
+    assert_eq!(error("math.pi(1);"), "Can only call functions and classes.");
+    assert_eq!(error("math.pi = 3;"), "Can't assign to 'math.pi'.");
+}
@@ -0,0 +1,74 @@
+use std::{cell::Cell, rc::Rc};
+
+use rlox::{errors::Error, interpreter::LoxValue, lox::Lox};
+
+fn lox_with_add() -> Lox {
+    let lox = Lox::new();
+    lox.define_native("add", 2, |args| {
+        let left = f64::try_from(args[0].clone()).map_err(|_| "Expected numbers.".to_string())?;
+        let right = f64::try_from(args[1].clone()).map_err(|_| "Expected numbers.".to_string())?;
+        Ok(LoxValue::from(left + right))
+    });
+    lox
+}
+
+#[test]
+fn scripts_call_natives() {
+    let lox = lox_with_add();
+    assert_eq!(lox.eval("add(1, add(2, 3));").unwrap(), LoxValue::from(6.0));
+}
+
+#[test]
+fn natives_can_use_host_state() {
+    let lox = Lox::new();
+    let calls = Rc::new(Cell::new(0));
+    let counter = calls.clone();
+    lox.define_native("tick", 0, move |_| {
+        counter.set(counter.get() + 1);
+        Ok(LoxValue::from(counter.get() as f64))
+    });
+
+    assert_eq!(lox.eval("tick(); tick();").unwrap(), LoxValue::from(2.0));
+    assert_eq!(calls.get(), 2);
+}
+
+#[test]
+fn arity_is_checked_before_the_call() {
+    let lox = lox_with_add();
+    match lox.eval("add(1);") {
+        Err(Error::RuntimeError(1, message)) => {
+            assert_eq!(message, "Expected 2 arguments but got 1.")
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn native_errors_are_runtime_errors_at_the_call() {
+    let lox = lox_with_add();
+    match lox.eval("\nadd(1, \"2\");") {
+        Err(Error::RuntimeError(2, message)) => assert_eq!(message, "Expected numbers."),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn only_functions_are_callable() {
+    let lox = Lox::new();
+    assert!(matches!(
+        lox.eval("\"not a function\"();"),
+        Err(Error::RuntimeError(1, _))
+    ));
+}
+
+#[test]
+fn natives_are_values() {
+    let lox = lox_with_add();
+    assert_eq!(
+        lox.eval("var plus = add; plus == add;").unwrap(),
+        LoxValue::from(true)
+    );
+    assert_eq!(lox.eval("add == 1;").unwrap(), LoxValue::from(false));
+    assert_eq!(lox.eval("!add;").unwrap(), LoxValue::from(false));
+    assert_eq!(lox.get_global("add").unwrap().stringify(), "<native fn>");
+}
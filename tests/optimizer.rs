@@ -0,0 +1,58 @@
+use rlox::{
+    ast::Stmt, ast_printer::AstPrinter, errors::Error, optimizer::Optimizer, parser::Parser,
+    scanner::Scanner,
+};
+
+fn parse(source: &str) -> Vec<Stmt> {
+    Parser::new(Scanner::new(source.to_string()))
+        .parse()
+        .unwrap_or_else(|error| panic!("{}", error))
+}
+
+/// `source` after optimizing, one S-expression per statement.
+fn optimize(source: &str) -> Vec<String> {
+    let mut statements = parse(source);
+    Optimizer::new()
+        .optimize(&mut statements)
+        .unwrap_or_else(|error| panic!("{}", error));
+    let printer = AstPrinter::new();
+    statements.iter().map(|stmt| printer.print(stmt)).collect()
+}
+
+#[test]
+fn constant_expressions_are_folded() {
+    assert_eq!(optimize("print 1 + 2 * 3;"), ["(print 7)"]);
+    assert_eq!(optimize("print -(4 - 6);"), ["(print 2)"]);
+    assert_eq!(optimize("print \"a\" + \"b\";"), ["(print \"ab\")"]);
+    assert_eq!(optimize("print !(1 < 2) == false;"), ["(print true)"]);
+}
+
+#[test]
+fn constants_fold_inside_other_expressions() {
+    assert_eq!(
+        optimize("var x = [1 + 1, f(2 * 2)];"),
+        ["(var x (list 2 (call f 4)))"]
+    );
+    assert_eq!(
+        optimize("for (i in range(0, 2 + 3)) print i * (2 - 1);"),
+        ["(for-in i (call range 0 5) (print (* i 1)))"]
+    );
+}
+
+#[test]
+fn expressions_with_variables_are_left_alone() {
+    assert_eq!(optimize("print a + 1;"), ["(print (+ a 1))"]);
+    assert_eq!(optimize("print -(a);"), ["(print (- (group a)))"]);
+    assert_eq!(optimize("print a + 1 + 2;"), ["(print (+ (+ a 1) 2))"]);
+}
+
+#[test]
+fn operations_that_would_fail_are_reported_up_front() {
+    let mut statements = parse("print 1;\nprint \"a\" - 1;");
+    let error = Optimizer::new().optimize(&mut statements).unwrap_err();
+
+    assert!(matches!(
+        error,
+        Error::OptimizerError(2, message) if message == "Operands must be numbers."
+    ));
+}
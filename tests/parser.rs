@@ -0,0 +1,86 @@
+use std::cell::Cell;
+
+use rlox::{ast_printer::AstPrinter, errors::Error, parser::Parser, scanner::Scanner};
+
+/// The single expression statement in `source`, as an S-expression.
+fn parse(source: &str) -> String {
+    let statements = Parser::new(Scanner::new(source.to_string()))
+        .parse()
+        .unwrap_or_else(|error| panic!("{}", error));
+    AstPrinter::new().print(&statements[0])
+}
+
+fn parse_error(source: &str) -> Error {
+    match Parser::new(Scanner::new(source.to_string())).parse() {
+        Ok(_) => panic!("expected {:?} not to parse", source),
+        Err(error) => error,
+    }
+}
+
+#[test]
+fn operators_bind_by_precedence() {
+    assert_eq!(parse("1 + 2 * 3;"), "(; (+ 1 (* 2 3)))");
+    assert_eq!(parse("1 * 2 + 3;"), "(; (+ (* 1 2) 3))");
+    assert_eq!(parse("1 < 2 == 3 > 4;"), "(; (== (< 1 2) (> 3 4)))");
+    assert_eq!(parse("-a.b * c[0];"), "(; (* (- (get b a)) (index c 0)))");
+    assert_eq!(parse("!f() == x;"), "(; (== (! (call f)) x))");
+    assert_eq!(parse("(1 + 2) * 3;"), "(; (* (group (+ 1 2)) 3))");
+}
+
+#[test]
+fn binary_operators_are_left_associative() {
+    assert_eq!(parse("1 - 2 - 3;"), "(; (- (- 1 2) 3))");
+    assert_eq!(parse("1 / 2 * 3;"), "(; (* (/ 1 2) 3))");
+    assert_eq!(parse("a == b != c;"), "(; (!= (== a b) c))");
+}
+
+#[test]
+fn assignment_and_unary_operators_are_right_associative() {
+    assert_eq!(parse("a = b = c;"), "(; (= a (= b c)))");
+    assert_eq!(parse("a.b = c[0] = 1;"), "(; (set b a (set-index c 0 1)))");
+    assert_eq!(parse("- - a;"), "(; (- (- a)))");
+    assert_eq!(parse("!!a;"), "(; (! (! a)))");
+}
+
+#[test]
+fn assignment_targets_must_be_variables_properties_or_items() {
+    for source in ["a + b = c;", "(a) = 1;", "f() = 1;", "1 = 2;", "-a = 1;"] {
+        assert!(
+            matches!(
+                parse_error(source),
+                Error::ParseError(1, message) if message == "Invalid assignment target"
+            ),
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn tokens_are_pulled_only_when_needed() {
+    let pulled = Cell::new(0);
+    let tokens = Scanner::new("print 1; print 2; print 3;".to_string()).inspect(|_| {
+        pulled.set(pulled.get() + 1);
+    });
+    let mut parser = Parser::new(tokens);
+
+    parser.next().unwrap().unwrap();
+    // `print 1 ;` and the `print` after it, which the parser peeks at.
+    assert_eq!(pulled.get(), 4);
+
+    parser.next().unwrap().unwrap();
+    assert_eq!(pulled.get(), 7);
+}
+
+#[test]
+fn scanning_stops_at_the_first_scanner_error() {
+    let pulled = Cell::new(0);
+    let tokens = Scanner::new("print 1; @ print 2; print 3;".to_string()).inspect(|_| {
+        pulled.set(pulled.get() + 1);
+    });
+
+    let result = Parser::new(tokens).parse();
+    assert!(matches!(result, Err(Error::ScannerError(1, _))));
+    // `print 1 ;` and the error; nothing after it is scanned.
+    assert_eq!(pulled.get(), 4);
+}
@@ -0,0 +1,129 @@
+use std::{
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+use rlox::{
+    capabilities::Capabilities,
+    errors::Error,
+    interpreter::{Interpreter, LoxValue},
+    lox::Lox,
+};
+
+mod common;
+
+use common::{error, error_in, eval, temp_path};
+
+#[test]
+fn type_names_each_kind_of_value() {
+    assert_eq!(eval("type(1);"), LoxValue::from("number"));
+    assert_eq!(eval("type(\"a\");"), LoxValue::from("string"));
+    assert_eq!(eval("type(true);"), LoxValue::from("boolean"));
+    assert_eq!(eval("type(nil);"), LoxValue::from("nil"));
+    assert_eq!(eval("type(type);"), LoxValue::from("function"));
+}
+
+#[test]
+fn str_matches_print() {
+    assert_eq!(eval("str(1.5);"), LoxValue::from("1.5"));
+    assert_eq!(eval("str(3) + \"!\";"), LoxValue::from("3!"));
+    assert_eq!(eval("str(nil);"), LoxValue::from("nil"));
+    assert_eq!(eval("str(str);"), LoxValue::from("<native fn>"));
+}
+
+#[test]
+fn num_parses_strings() {
+    assert_eq!(eval("num(\" 42 \") + 1;"), LoxValue::from(43.0));
+    assert_eq!(eval("num(-2.5);"), LoxValue::from(-2.5));
+    assert_eq!(
+        error("num(\"abc\");"),
+        "Could not convert 'abc' to a number."
+    );
+    assert_eq!(
+        error("num(true);"),
+        "num() expects a number or string but got boolean."
+    );
+}
+
+#[test]
+fn len_counts_characters() {
+    assert_eq!(eval("len(\"\");"), LoxValue::from(0.0));
+    let lox = Lox::new();
+    lox.set_global("word", "héllo");
+    assert_eq!(lox.eval("len(word);").unwrap(), LoxValue::from(5.0));
+    assert_eq!(
+        error("len(1);"),
+        "len() expects a string, list or map but got number."
+    );
+}
+
+#[test]
+fn input_reads_lines_until_the_end() {
+    let interpreter = Interpreter::with_io(Box::new(io::sink()), Box::new(&b"ada\r\nbob"[..]));
+    let lox = Lox::with_interpreter(interpreter);
+
+    assert_eq!(lox.eval("input();").unwrap(), LoxValue::from("ada"));
+    assert_eq!(lox.eval("input();").unwrap(), LoxValue::from("bob"));
+    assert_eq!(lox.eval("input();").unwrap(), LoxValue::nil());
+}
+
+#[test]
+fn assert_fails_with_its_message() {
+    assert_eq!(eval("assert(1 < 2, \"math\");"), LoxValue::nil());
+    assert_eq!(
+        error("assert(nil, \"no value\");"),
+        "Assertion failed: no value"
+    );
+}
+
+#[test]
+fn exit_needs_the_capability() {
+    assert_eq!(error("exit(3);"), "Script lacks the 'exit' capability.");
+
+    let lox = Lox::new();
+    lox.interpreter()
+        .set_capabilities(Capabilities::none().allow_exit());
+    assert!(matches!(
+        lox.eval("var a = 1; exit(3); a = 2;"),
+        Err(Error::Exit(3))
+    ));
+    assert_eq!(lox.get_global("a"), Some(LoxValue::from(1.0)));
+    assert_eq!(
+        error_in(&lox, "exit(1.5);"),
+        "exit() expects an integer but got 1.5."
+    );
+}
+
+#[test]
+fn misuse_is_an_arity_error() {
+    assert_eq!(error("len();"), "Expected 1 arguments but got 0.");
+}
+
+#[test]
+fn the_cli_reads_input_from_stdin() {
+    let path = temp_path("prelude-input.lox");
+    std::fs::write(
+        &path,
+        "print input() + \"!\";\nprint input();\nprint input();",
+    )
+    .unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .arg(&path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"first\nsecond\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "first!\nsecond\nnil\n"
+    );
+}
@@ -0,0 +1,210 @@
+#![cfg(feature = "serde")]
+
+use std::collections::BTreeMap;
+
+use rlox::{
+    errors::Error,
+    interpreter::LoxValue,
+    lox::Lox,
+    serde_value::{from_value, to_value},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Player {
+    name: String,
+    level: u32,
+    score: f64,
+    guild: Option<String>,
+    tags: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+enum Shape {
+    Empty,
+    Circle(f64),
+    Rect { width: f64, height: f64 },
+}
+
+fn player() -> Player {
+    Player {
+        name: "ada".to_string(),
+        level: 3,
+        score: 12.5,
+        guild: None,
+        tags: vec!["a".to_string(), "b".to_string()],
+    }
+}
+
+#[test]
+fn structs_round_trip() {
+    let value = to_value(&player()).unwrap();
+    assert_eq!(value.stringify(), "Player instance");
+    assert_eq!(from_value::<Player>(value).unwrap(), player());
+}
+
+#[test]
+fn scripts_see_struct_fields() {
+    let lox = Lox::new();
+    lox.set_global("player", to_value(&player()).unwrap());
+
+    assert_eq!(
+        lox.eval("player.name + \"!\";").unwrap(),
+        LoxValue::from("ada!")
+    );
+    assert_eq!(lox.eval("player.guild;").unwrap(), LoxValue::nil());
+    assert_eq!(lox.eval("player.tags;").unwrap().stringify(), "[a, b]");
+
+    lox.eval("player.level = player.level + 1;").unwrap();
+    let updated: Player = from_value(lox.get_global("player").unwrap()).unwrap();
+    assert_eq!(updated.level, 4);
+}
+
+#[test]
+fn enums_are_externally_tagged() {
+    assert_eq!(to_value(&Shape::Empty).unwrap(), LoxValue::from("Empty"));
+
+    for shape in [
+        Shape::Empty,
+        Shape::Circle(2.0),
+        Shape::Rect {
+            width: 1.0,
+            height: 2.0,
+        },
+    ] {
+        let value = to_value(&shape).unwrap();
+        assert_eq!(from_value::<Shape>(value).unwrap(), shape);
+    }
+}
+
+#[test]
+fn maps_become_maps() {
+    let mut scores = BTreeMap::new();
+    scores.insert("ada".to_string(), 3.0);
+    scores.insert("bob".to_string(), 4.0);
+
+    let value = to_value(&scores).unwrap();
+    assert_eq!(value.stringify(), "{\"ada\": 3, \"bob\": 4}");
+    assert_eq!(from_value::<BTreeMap<String, f64>>(value).unwrap(), scores);
+
+    let mut names = BTreeMap::new();
+    names.insert(1, "one".to_string());
+    names.insert(2, "two".to_string());
+    let value = to_value(&names).unwrap();
+    assert_eq!(value.stringify(), "{1: one, 2: two}");
+    assert_eq!(from_value::<BTreeMap<i32, String>>(value).unwrap(), names);
+}
+
+#[test]
+fn script_maps_convert_back() {
+    let lox = Lox::new();
+    let value = lox.eval("var m = {\"x\": 1}; m[\"y\"] = 2; m;").unwrap();
+    let map: BTreeMap<String, f64> = from_value(value).unwrap();
+    assert_eq!(map.get("y"), Some(&2.0));
+}
+
+#[test]
+fn map_keys_must_be_hashable() {
+    let mut map = BTreeMap::new();
+    map.insert(vec![1], "one");
+
+    assert!(matches!(to_value(&map), Err(Error::ConversionError(_))));
+}
+
+#[test]
+fn integers_must_be_whole_and_in_range() {
+    assert_eq!(from_value::<u8>(LoxValue::from(255.0)).unwrap(), 255);
+    assert!(from_value::<u8>(LoxValue::from(256.0)).is_err());
+    assert!(from_value::<i32>(LoxValue::from(1.5)).is_err());
+    assert!(from_value::<u32>(LoxValue::from(-1.0)).is_err());
+}
+
+#[test]
+fn integers_stop_short_of_the_next_power_of_two() {
+    let two_to_the = |exponent| 2f64.powi(exponent);
+
+    // The largest doubles below 2^64 and 2^63 are 2048 and 1024 short of them.
+    assert_eq!(
+        from_value::<u64>(LoxValue::from(two_to_the(64) - 2048.0)).unwrap(),
+        u64::MAX - 2047
+    );
+    assert!(from_value::<u64>(LoxValue::from(two_to_the(64))).is_err());
+    assert_eq!(
+        from_value::<i64>(LoxValue::from(two_to_the(63) - 1024.0)).unwrap(),
+        i64::MAX - 1023
+    );
+    assert!(from_value::<i64>(LoxValue::from(two_to_the(63))).is_err());
+    assert_eq!(
+        from_value::<i64>(LoxValue::from(-two_to_the(63))).unwrap(),
+        i64::MIN
+    );
+    assert!(from_value::<i8>(LoxValue::from(128.0)).is_err());
+    assert_eq!(from_value::<i8>(LoxValue::from(-128.0)).unwrap(), i8::MIN);
+}
+
+#[test]
+fn lox_values_serialize_to_json() {
+    let lox = Lox::new();
+    lox.set_global("player", to_value(&player()).unwrap());
+    let value = lox.eval("player;").unwrap();
+
+    assert_eq!(
+        serde_json::to_string(&value).unwrap(),
+        r#"{"name":"ada","level":3.0,"score":12.5,"guild":null,"tags":["a","b"]}"#
+    );
+}
+
+#[test]
+fn values_that_contain_themselves_cannot_be_serialized() {
+    let lox = Lox::new();
+    let list = lox.eval("var xs = [1]; xs.push([xs]); xs;").unwrap();
+    let error = serde_json::to_string(&list).unwrap_err();
+    assert!(error.to_string().contains("contains itself"), "{}", error);
+    assert!(matches!(to_value(&list), Err(Error::ConversionError(_))));
+
+    lox.set_global("player", to_value(&player()).unwrap());
+    let record = lox.eval("player.tags = [player]; player;").unwrap();
+    assert!(serde_json::to_string(&record).is_err());
+
+    let map = lox.eval("var m = {}; m[\"m\"] = m; m;").unwrap();
+    assert!(serde_json::to_string(&map).is_err());
+
+    let repeated = lox.eval("var a = [1]; [a, a];").unwrap();
+    assert_eq!(serde_json::to_string(&repeated).unwrap(), "[[1.0],[1.0]]");
+}
+
+#[test]
+fn values_that_contain_themselves_cannot_be_deserialized() {
+    let lox = Lox::new();
+    let list = lox.eval("var xs = [1]; xs.push(xs); xs;").unwrap();
+    assert!(matches!(
+        from_value::<serde_json::Value>(list),
+        Err(Error::ConversionError(message)) if message == "Can't deserialize a value that contains itself."
+    ));
+
+    lox.set_global("player", to_value(&player()).unwrap());
+    let record = lox.eval("player.guild = player; player;").unwrap();
+    assert!(from_value::<serde_json::Value>(record).is_err());
+
+    let map = lox.eval("var m = {}; m[\"m\"] = [m]; m;").unwrap();
+    assert!(from_value::<serde_json::Value>(map).is_err());
+
+    let repeated = lox.eval("var a = [1]; [a, a];").unwrap();
+    assert_eq!(
+        from_value::<Vec<Vec<u8>>>(repeated).unwrap(),
+        vec![vec![1], vec![1]]
+    );
+}
+
+#[test]
+fn functions_cannot_be_converted() {
+    let lox = Lox::new();
+    lox.define_native("f", 0, |_| Ok(LoxValue::nil()));
+    let function = lox.get_global("f").unwrap();
+
+    assert!(serde_json::to_string(&function).is_err());
+    assert!(matches!(
+        from_value::<f64>(function),
+        Err(Error::ConversionError(_))
+    ));
+}
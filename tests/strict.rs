@@ -0,0 +1,135 @@
+use rlox::{
+    ast::Stmt, errors::Error, interpreter::LoxValue, lox::Lox, parser::Parser, scanner::Scanner,
+    strict::StrictChecker,
+};
+
+fn parse(source: &str) -> Vec<Stmt> {
+    Parser::new(Scanner::new(source.to_string()))
+        .parse()
+        .unwrap_or_else(|error| panic!("{}", error))
+}
+
+fn check(source: &str) -> Result<(), Error> {
+    StrictChecker::new().check(&parse(source))
+}
+
+/// The message of the strict error `source` is rejected with, checking it is
+/// reported at `line`.
+fn rejection(source: &str, line: usize) -> String {
+    match check(source) {
+        Err(Error::StrictError(at, message)) if at == line => message,
+        other => panic!(
+            "expected a strict error at line {} but got {:?}",
+            line, other
+        ),
+    }
+}
+
+#[test]
+fn reading_an_unassigned_variable_is_rejected() {
+    assert_eq!(
+        rejection("var a;\nprint a;", 2),
+        "Variable 'a' is read before being assigned."
+    );
+    assert_eq!(
+        rejection("var a; var b = 1;\nprint b + a;", 2),
+        "Variable 'a' is read before being assigned."
+    );
+}
+
+#[test]
+fn reads_are_found_inside_any_expression() {
+    for source in [
+        "var a; f(a);",
+        "var a; print -a;",
+        "var a; print [1, a];",
+        "var a; print {\"k\": a};",
+        "var a; print a.b;",
+        "var a; xs[a] = 1;",
+        "var a; var b = a;",
+        "var a; a = a;",
+        "var a; for (x in a) print x;",
+    ] {
+        assert!(
+            matches!(check(source), Err(Error::StrictError(1, _))),
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn the_first_error_is_reported() {
+    assert_eq!(
+        rejection("var a; var b;\nprint a;\nprint b;", 2),
+        "Variable 'a' is read before being assigned."
+    );
+}
+
+#[test]
+fn assigned_variables_are_accepted() {
+    for source in [
+        "var a = 1; print a;",
+        "var a; a = 2; print a;",
+        "var a; var b = a = 3; print a + b;",
+        "var a; print a = 1;",
+        "var xs = []; for (x in xs) print x;",
+        "var a; { a = 1; } print a;",
+    ] {
+        assert!(check(source).is_ok(), "{}", source);
+    }
+}
+
+#[test]
+fn variables_are_scoped_to_their_block() {
+    assert!(check("var x = 1; { var x; } print x;").is_ok());
+    assert!(check("var x; { var x = 1; print x; }").is_ok());
+    assert_eq!(
+        rejection("var x = 1;\n{ var x;\nprint x; }", 3),
+        "Variable 'x' is read before being assigned."
+    );
+    assert_eq!(
+        rejection("var x; { var x = 1; }\nprint x;", 2),
+        "Variable 'x' is read before being assigned."
+    );
+}
+
+#[test]
+fn the_loop_variable_is_scoped_to_the_loop() {
+    assert!(check("var i; for (i in [1]) print i;").is_ok());
+    assert_eq!(
+        rejection("var i; for (i in [1]) print i;\nprint i;", 2),
+        "Variable 'i' is read before being assigned."
+    );
+}
+
+#[test]
+fn assignments_in_a_loop_body_may_never_happen() {
+    assert_eq!(
+        rejection("var x; for (i in []) x = i;\nprint x;", 2),
+        "Variable 'x' is read before being assigned."
+    );
+    assert_eq!(
+        rejection("var x; for (i in []) { x = i; }\nprint x;", 2),
+        "Variable 'x' is read before being assigned."
+    );
+    assert!(check("var x; for (i in [1]) { x = i; print x; }").is_ok());
+    assert!(check("var x; x = 0; for (i in []) x = i; print x;").is_ok());
+}
+
+#[test]
+fn writing_an_unassigned_variable_is_accepted() {
+    assert!(check("var a; a = 1;").is_ok());
+    assert!(check("var o; o = {}; o[\"k\"] = 1;").is_ok());
+}
+
+#[test]
+fn globals_defined_by_the_host_are_left_to_runtime() {
+    let source = "print answer + 1;";
+    assert!(check(source).is_ok());
+
+    let lox = Lox::new();
+    lox.set_global("answer", 41.0);
+    assert_eq!(lox.eval("answer + 1;").unwrap(), LoxValue::from(42.0));
+    assert!(check("var total = answer; print total;").is_ok());
+}
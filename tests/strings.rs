@@ -0,0 +1,106 @@
+use rlox::interpreter::LoxValue;
+
+mod common;
+
+use common::{error, eval};
+
+#[test]
+fn source_can_contain_unicode() {
+    assert_eq!(eval("\"wörld ✓\";"), LoxValue::from("wörld ✓"));
+    assert_eq!(eval("// ünïcode\n1;"), LoxValue::from(1.0));
+}
+
+#[test]
+fn lengths_and_indexes_count_characters() {
+    assert_eq!(eval("\"héllo\".len();"), LoxValue::from(5.0));
+    assert_eq!(eval("\"héllo\".substring(1, 3);"), LoxValue::from("él"));
+    assert_eq!(
+        eval("\"naïve café\".indexOf(\"café\");"),
+        LoxValue::from(6.0)
+    );
+    assert_eq!(eval("\"abc\".indexOf(\"z\");"), LoxValue::from(-1.0));
+    assert_eq!(eval("\"é\".charCodeAt(0);"), LoxValue::from(233.0));
+    assert_eq!(eval("fromCharCode(10003);"), LoxValue::from("✓"));
+}
+
+#[test]
+fn case_and_whitespace() {
+    assert_eq!(
+        eval("\"  Ünïcode \".trim().upper();"),
+        LoxValue::from("ÜNÏCODE")
+    );
+    assert_eq!(eval("\"ABC\".lower();"), LoxValue::from("abc"));
+}
+
+#[test]
+fn searching_and_replacing() {
+    assert_eq!(eval("\"lox.rs\".endsWith(\".rs\");"), LoxValue::from(true));
+    assert_eq!(
+        eval("\"lox.rs\".startsWith(\"rs\");"),
+        LoxValue::from(false)
+    );
+    assert_eq!(
+        eval("\"a-b-c\".replace(\"-\", \" + \");"),
+        LoxValue::from("a + b + c")
+    );
+    assert_eq!(eval("\"ab\".repeat(3);"), LoxValue::from("ababab"));
+    assert_eq!(eval("\"ab\".repeat(0);"), LoxValue::from(""));
+}
+
+#[test]
+fn huge_repeats_fail_before_allocating() {
+    assert_eq!(
+        error("\"abcd\".repeat(4294967295);"),
+        "Repeated string is too long."
+    );
+}
+
+#[test]
+fn split_and_join() {
+    assert_eq!(eval("\"a,b,,c\".split(\",\");").stringify(), "[a, b, , c]");
+    assert_eq!(eval("\"añb\".split(\"\");").stringify(), "[a, ñ, b]");
+    assert_eq!(eval("len(\"a b c\".split(\" \"));"), LoxValue::from(3.0));
+    assert_eq!(
+        eval("\" / \".join(\"x y z\".split(\" \"));"),
+        LoxValue::from("x / y / z")
+    );
+}
+
+#[test]
+fn methods_are_bound_values() {
+    assert_eq!(
+        eval("var shout = \"hey\".upper; shout() + \"!\";"),
+        LoxValue::from("HEY!")
+    );
+}
+
+#[test]
+fn misuse_is_a_runtime_error() {
+    assert_eq!(error("\"abc\".reverse();"), "Undefined property 'reverse'.");
+    assert_eq!(
+        error("\"abc\".upper(1);"),
+        "Expected 0 arguments but got 1."
+    );
+    assert_eq!(
+        error("\"abc\".substring(2, 5);"),
+        "Substring range out of bounds."
+    );
+    assert_eq!(
+        error("\"abc\".substring(-1, 2);"),
+        "substring() expects a non-negative integer but got -1."
+    );
+    assert_eq!(
+        error("\"abc\".indexOf(1);"),
+        "indexOf() expects a string but got number."
+    );
+    assert_eq!(
+        error("\",\".join(\"abc\");"),
+        "join() expects a list but got string."
+    );
+    assert_eq!(error("\"abc\".charCodeAt(3);"), "Index out of bounds.");
+    assert_eq!(
+        error("fromCharCode(55296);"),
+        "55296 is not a valid character code."
+    );
+    assert_eq!(error("\"abc\".size = 1;"), "Only instances have fields.");
+}
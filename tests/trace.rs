@@ -0,0 +1,161 @@
+use std::rc::Rc;
+
+use rlox::{
+    capabilities::Capabilities,
+    errors::{Error, Frame},
+    host::Namespace,
+    interpreter::LoxValue,
+    lox::Lox,
+    native::NativeFunction,
+    scanner::Scanner,
+};
+
+/// A Lox with a `run(source)` native that evaluates `source` in the same
+/// interpreter, so calls can nest. It passes the errors it gets on.
+fn nesting_lox() -> Rc<Lox> {
+    let lox = Rc::new(Lox::new());
+
+    let weak = Rc::downgrade(&lox);
+    lox.define_native("run", 1, move |args| {
+        let source = String::try_from(args[0].clone()).unwrap();
+        Ok(weak.upgrade().unwrap().eval(&source)?)
+    });
+
+    lox
+}
+
+fn frame(function: &str, line: usize, column: usize) -> Frame {
+    Frame {
+        function: function.to_string(),
+        line,
+        column,
+    }
+}
+
+#[test]
+fn tokens_know_their_columns() {
+    let columns: Vec<_> = Scanner::new("var ab = 1;\n  print \"x\ny\" + ab;".to_string())
+        .map(|token| {
+            let token = token.unwrap();
+            (token.lexeme, token.line, token.column)
+        })
+        .collect();
+
+    assert_eq!(
+        columns,
+        [
+            ("var", 1, 1),
+            ("ab", 1, 5),
+            ("=", 1, 8),
+            ("1", 1, 10),
+            (";", 1, 11),
+            ("print", 2, 3),
+            ("\"x\ny\"", 2, 9),
+            ("+", 3, 4),
+            ("ab", 3, 6),
+            (";", 3, 8),
+            ("", 3, 9),
+        ]
+        .map(|(lexeme, line, column)| (lexeme.to_string(), line, column))
+    );
+}
+
+#[test]
+fn top_level_errors_have_no_trace() {
+    let lox = Lox::new();
+    lox.define_native("fail", 0, |_| Err("Nope.".into()));
+
+    let error = lox.eval("fail();").unwrap_err();
+    assert!(error.trace().is_empty());
+    assert!(matches!(error, Error::RuntimeError(1, _)));
+}
+
+#[test]
+fn errors_inside_calls_carry_the_call_stack() {
+    let lox = nesting_lox();
+
+    // Lox strings can't contain quotes, so the innermost source is a global.
+    lox.set_global("inner", "-nil;");
+    let error = lox.eval("\n  run(\"run(inner);\");").unwrap_err();
+
+    assert_eq!(error.trace(), [frame("run", 2, 3), frame("run", 1, 1)]);
+    assert!(matches!(
+        error.cause(),
+        Error::RuntimeError(1, message) if message == "Operand must be a number."
+    ));
+}
+
+#[test]
+fn traces_print_most_recent_call_last() {
+    let lox = nesting_lox();
+
+    let error = lox.eval("run(\"undefined;\");").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Traceback (most recent call last):\n  \
+         [line 1, column 1] in run()\n\
+         [line 1], RuntimeError: Undefined variable 'undefined'."
+    );
+}
+
+#[test]
+fn a_failing_native_is_reported_at_its_call_site() {
+    let lox = nesting_lox();
+    lox.define_native("fail", 0, |_| Err("Nope.".into()));
+
+    let error = lox.eval("run(\"\n\nfail();\");").unwrap_err();
+    assert_eq!(error.trace(), [frame("run", 1, 1)]);
+    assert!(matches!(
+        error.cause(),
+        Error::RuntimeError(3, message) if message == "Nope."
+    ));
+}
+
+#[test]
+fn host_methods_get_frames_too() {
+    let lox = Rc::new(Lox::new());
+    let weak = Rc::downgrade(&lox);
+    let runner = Namespace::new(
+        "Runner",
+        vec![(
+            "run".to_string(),
+            LoxValue::NativeFunction(Rc::new(NativeFunction::new("run", 1, move |args| {
+                let source = String::try_from(args[0].clone()).unwrap();
+                Ok(weak.upgrade().unwrap().eval(&source)?)
+            }))),
+        )],
+    );
+    lox.set_global("runner", LoxValue::host_object(runner));
+
+    let error = lox.eval("  runner.run(\"nil.x;\");").unwrap_err();
+    assert_eq!(error.trace(), [frame("Runner.run", 1, 10)]);
+}
+
+#[test]
+fn exits_and_limits_pass_through_untraced() {
+    let lox = nesting_lox();
+    lox.interpreter()
+        .set_capabilities(Capabilities::none().allow_exit());
+    lox.set_global("inner", "exit(3);");
+
+    assert!(matches!(
+        lox.eval("run(\"run(inner);\");"),
+        Err(Error::Exit(3))
+    ));
+
+    lox.interpreter().set_fuel(Some(20));
+    assert!(matches!(
+        lox.eval("run(\"1; 2; 3; 4; 5; 6; 7; 8; 9; 10; 11; 12;\");"),
+        Err(Error::OutOfFuel)
+    ));
+}
+
+#[test]
+fn traces_are_not_kept_between_runs() {
+    let lox = nesting_lox();
+
+    assert_eq!(lox.eval("run(\"1;\");").unwrap(), LoxValue::from(1.0));
+    assert!(lox.eval("run(\"-nil;\");").is_err());
+    let error = lox.eval("-nil;").unwrap_err();
+    assert!(error.trace().is_empty());
+}
@@ -0,0 +1,96 @@
+use std::{fs, process::Command};
+
+mod common;
+use common::temp_path;
+
+struct Output {
+    stdout: String,
+    stderr: String,
+    success: bool,
+}
+
+/// Runs `source` through the `rlox` binary, with and without `-O`, and checks
+/// that constant folding doesn't change the outcome.
+fn run(name: &str, source: &str) -> Output {
+    let path = temp_path(&format!("unary-{}.lox", name));
+    fs::write(&path, source).unwrap();
+
+    let run_with = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_rlox"))
+            .args(args)
+            .arg(&path)
+            .output()
+            .unwrap();
+        Output {
+            stdout: String::from_utf8(output.stdout).unwrap(),
+            stderr: String::from_utf8(output.stderr).unwrap(),
+            success: output.status.success(),
+        }
+    };
+
+    let plain = run_with(&[]);
+    let optimized = run_with(&["-O"]);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(plain.success, optimized.success);
+    // Folding reports type errors before anything runs, so only successful
+    // runs are expected to print the same thing.
+    if plain.success {
+        assert_eq!(plain.stdout, optimized.stdout);
+    }
+    plain
+}
+
+#[test]
+fn minus_negates_numbers() {
+    let output = run(
+        "minus",
+        "print -5;\nprint -(-5);\nprint --5;\nprint -(2 + 3) * 2;\n",
+    );
+    assert!(output.success);
+    assert_eq!(output.stdout, "-5\n5\n5\n-10\n");
+}
+
+#[test]
+fn minus_on_a_variable() {
+    let output = run("minus-variable", "var a = 3;\nprint -a;\n");
+    assert_eq!(output.stdout, "-3\n");
+}
+
+#[test]
+fn bang_negates_truthiness() {
+    let output = run(
+        "bang",
+        "print !true;\nprint !false;\nprint !nil;\nprint !0;\nprint !\"\";\nprint !!nil;\nprint !!123;\n",
+    );
+    assert!(output.success);
+    assert_eq!(
+        output.stdout,
+        "false\ntrue\ntrue\nfalse\nfalse\nfalse\ntrue\n"
+    );
+}
+
+#[test]
+fn only_nil_and_false_are_falsey() {
+    let output = run(
+        "truthiness",
+        "print !!false;\nprint !!nil;\nprint !!true;\nprint !!0;\nprint !!\"false\";\n",
+    );
+    assert_eq!(output.stdout, "false\nfalse\ntrue\ntrue\ntrue\n");
+}
+
+#[test]
+fn minus_rejects_non_numbers() {
+    for (name, operand) in [("string", "\"a\""), ("nil", "nil"), ("bool", "true")] {
+        let output = run(
+            &format!("minus-{}", name),
+            &format!("print 1;\nprint -{};\nprint 2;\n", operand),
+        );
+        assert!(!output.success);
+        assert_eq!(output.stdout, "1\n");
+        assert!(
+            output.stderr.contains("Operand must be a number."),
+            "{}",
+            output.stderr
+        );
+    }
+}